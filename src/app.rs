@@ -1,9 +1,16 @@
-use std::{path::PathBuf, sync::mpsc, thread};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    sync::{Arc, mpsc},
+    thread,
+};
 
 use crate::{
+    data, events, export,
     games::{self, Game},
+    server, stardb, tasks,
     themes::{self, Theme},
-    ui,
+    ui, vault, webhook,
 };
 
 pub enum State {
@@ -14,22 +21,58 @@ pub enum State {
     Waiting(String),
     PullMenu,
     Game,
-    Achievements(Vec<u32>),
-    Artifacts(Vec<crate::games::Artifact>),
+    Achievements(Vec<u32>, BTreeMap<u32, String>),
+    Artifacts(Artifacts),
     Pulls(String),
+    Snapshots(Box<crate::snapshots::Report>),
     Error(String),
 }
 
 pub enum Message {
     GoTo(State),
+    // The cached last capture, shown again without saving or sending it.
+    Restored(State),
     Game(Game),
     Theme(Theme),
+    Language(data::Language),
+    Weights(games::analytics::Weights),
     #[cfg(not(debug_assertions))]
     Updated(Option<self_update::Status>),
-    User(Option<User>),
+    // Logged in, becomes the active account.
+    User(User),
+    // A renewed cookie for a saved account.
+    Renewed(User),
+    // The account's session expired, it is removed.
+    Expired(String),
+    // stardb couldn't renew the cookie, the saved one is kept. Username and
+    // error.
+    NotRenewed(String, String),
+    SwitchAccount(String),
+    // Binds the current game's UID to an account, `None` to unbind.
+    Bind(Option<String>),
+    // A pull import found the UID behind the account it went to, or a
+    // capture read it from the game, `None` for the active account.
+    DetectedUid(Game, String, Option<String>),
     Path(PathBuf),
+    Uid(Game, String),
+    AutoSaveDir(Option<PathBuf>),
+    Server(bool),
+    Hooks(String),
+    Webhook(webhook::Webhook),
+    SyncPreview(Option<stardb::SyncDiff>),
+    Unlock(String),
+    Passphrase(Option<String>),
+    ForgetEverything,
+    PendingSyncs(usize),
+    // Time to retry the queued syncs that are due.
+    RetrySyncs,
+    // A retry finished, with how many syncs are still waiting if the queue
+    // could be read.
+    SyncsRetried(Option<usize>),
+    Progress(games::Progress),
     Logout,
     Toast(egui_notify::Toast),
+    Tables(Arc<data::Tables>),
 }
 
 pub struct App {
@@ -39,18 +82,165 @@ pub struct App {
     pub game: games::Game,
     pub toasts: egui_notify::Toasts,
     pub theme: themes::Theme,
-    pub user: Option<User>,
+    pub language: data::Language,
+    pub weights: games::analytics::Weights,
+    pub accounts: Accounts,
+    pub vault: Option<vault::Vault>,
+    // Only kept in memory, the vault is re-encrypted with it on changes.
+    pub passphrase: Option<String>,
+    // The vault needs the passphrase before the session can be used.
+    pub session_locked: bool,
     pub paths: Paths,
+    pub uids: Uids,
+    pub auto_save_dir: Option<PathBuf>,
+    // Post-export commands, one per line.
+    pub hooks: String,
+    pub webhook: webhook::Webhook,
+    pub server: Option<server::Server>,
+    pub events: Option<events::EventLog>,
+    pub tables: Option<Arc<data::Tables>>,
+    // Changes an achievement sync would make, until it is applied or cancelled.
+    pub sync_preview: Option<stardb::SyncDiff>,
+    // Failed syncs waiting in the retry queue.
+    pub pending_syncs: usize,
+    // Accounts whose cookie is being renewed. Queued syncs wait for them,
+    // since they use the current cookie.
+    pub renewing: BTreeSet<String>,
+    pub retrying_syncs: bool,
+    // The first retry after launch or a login ignores the backoff.
+    pub retried_syncs: bool,
     pub account_popup_open: bool,
     pub theme_popup_open: bool,
+    pub language_popup_open: bool,
+}
+
+pub struct Artifacts {
+    pub artifacts: Vec<games::Artifact>,
+    pub warnings: Vec<String>,
+    // When they were captured, tells the table state of captures apart.
+    pub timestamp: u64,
+    // Validation problems by artifact index, found once when captured.
+    pub problems: BTreeMap<usize, Vec<String>>,
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+impl Artifacts {
+    pub fn new(artifacts: Vec<games::Artifact>, warnings: Vec<String>, timestamp: u64) -> Self {
+        let problems = games::validate::validate_all(&artifacts)
+            .into_iter()
+            .collect();
+
+        Self {
+            artifacts,
+            warnings,
+            timestamp,
+            problems,
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct User {
     pub id: String,
     pub username: String,
 }
 
+// Saved stardb accounts, with the UIDs that sync to each of them.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct Accounts {
+    pub users: Vec<User>,
+    // Username of the account for UIDs without a binding.
+    pub active: Option<String>,
+    // Usernames by `game:uid`. Kept when an account expires, so that logging
+    // back in restores them.
+    pub bindings: BTreeMap<String, String>,
+}
+
+impl Accounts {
+    pub fn active(&self) -> Option<&User> {
+        self.get(self.active.as_deref()?)
+    }
+
+    pub fn get(&self, username: &str) -> Option<&User> {
+        self.users.iter().find(|u| u.username == username)
+    }
+
+    // The username the UID is bound to, also when that account is logged out.
+    pub fn binding(&self, game: Game, uid: &str) -> Option<&str> {
+        self.bindings
+            .get(&binding_key(game, uid))
+            .map(String::as_str)
+    }
+
+    // The username the UID is bound to while that account is logged out.
+    pub fn logged_out(&self, game: Game, uid: Option<&str>) -> Option<&str> {
+        self.binding(game, uid?)
+            .filter(|username| self.get(username).is_none())
+    }
+
+    // The account syncs of the UID go to. `None` while the UID's bound account
+    // is logged out, rather than syncing to the active one.
+    pub fn for_uid(&self, game: Game, uid: Option<&str>) -> Option<&User> {
+        match uid.and_then(|uid| self.binding(game, uid)) {
+            Some(username) => self.get(username),
+            None => self.active(),
+        }
+    }
+
+    fn upsert(&mut self, user: User) {
+        match self.users.iter_mut().find(|u| u.username == user.username) {
+            Some(saved) => *saved = user,
+            None => self.users.push(user),
+        }
+    }
+
+    // Puts `newer` on top, e.g. logins made while the vault was still locked.
+    fn merge(&mut self, newer: Accounts) {
+        for user in newer.users {
+            self.upsert(user);
+        }
+        if newer.active.is_some() {
+            self.active = newer.active;
+        }
+        self.bindings.extend(newer.bindings);
+    }
+
+    fn remove(&mut self, username: &str) -> Option<User> {
+        let i = self.users.iter().position(|u| u.username == username)?;
+        let user = self.users.remove(i);
+
+        if self.active.as_deref() == Some(username) {
+            self.active = self.users.first().map(|u| u.username.clone());
+        }
+
+        Some(user)
+    }
+}
+
+fn binding_key(game: Game, uid: &str) -> String {
+    format!("{}:{uid}", game.id())
+}
+
+// What the vault holds. Before accounts there was a single optional user.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum Saved {
+    Accounts(Accounts),
+    User(Option<User>),
+}
+
+impl From<Saved> for Accounts {
+    fn from(saved: Saved) -> Self {
+        match saved {
+            Saved::Accounts(accounts) => accounts,
+            Saved::User(user) => Accounts {
+                active: user.as_ref().map(|u| u.username.clone()),
+                users: user.into_iter().collect(),
+                bindings: BTreeMap::new(),
+            },
+        }
+    }
+}
+
 #[derive(Default, serde::Serialize, serde::Deserialize)]
 pub struct Paths {
     pub zzz: Option<PathBuf>,
@@ -58,8 +248,27 @@ pub struct Paths {
     pub gi: Option<PathBuf>,
 }
 
+// Player UIDs, entered by the user or read from the game after a capture.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct Uids {
+    pub zzz: Option<String>,
+    pub hsr: Option<String>,
+    pub gi: Option<String>,
+}
+
+impl Uids {
+    pub fn get(&self, game: Game) -> Option<String> {
+        match game {
+            Game::Hsr => self.hsr.clone(),
+            Game::Gi => self.gi.clone(),
+            Game::Zzz => self.zzz.clone(),
+        }
+        .filter(|uid| !uid.is_empty())
+    }
+}
+
 impl App {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>, events: Option<events::EventLog>) -> Self {
         egui_extras::install_image_loaders(&cc.egui_ctx);
 
         let mut fonts = egui::FontDefinitions::default();
@@ -90,56 +299,94 @@ impl App {
             .and_then(|s| eframe::get_value(s, "theme"))
             .unwrap_or_default();
 
-        let user: Option<User> = cc
+        let language: data::Language = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, "language"))
+            .unwrap_or_default();
+
+        let weights: games::analytics::Weights = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, "weights"))
+            .unwrap_or_default();
+
+        let vault = crate::storage_dir()
+            .map(|dir| vault::Vault::new(&dir))
+            .map_err(|e| tracing::warn!("No session storage: {e}"))
+            .ok();
+
+        // Older versions kept the session in plaintext, it moves to the vault.
+        let plaintext_user: Option<User> = cc
             .storage
             .and_then(|s| eframe::get_value(s, "user").unwrap_or_default());
+        let session_locked =
+            plaintext_user.is_none() && vault.as_ref().is_some_and(|v| v.is_locked());
+        let accounts: Accounts = match (plaintext_user, &vault) {
+            (Some(user), Some(vault)) => {
+                let accounts = Accounts::from(Saved::User(Some(user)));
+                if let Err(e) = vault.save(&accounts, None) {
+                    tracing::warn!("Couldn't move the session to the vault: {e}");
+                }
+                accounts
+            }
+            (user, _) if session_locked => Saved::User(user).into(),
+            (None, Some(vault)) => match vault.load::<Saved>(None) {
+                Ok(saved) => saved.map(Accounts::from).unwrap_or_default(),
+                Err(e) => {
+                    tracing::warn!("Couldn't load the session: {e}");
+                    Accounts::default()
+                }
+            },
+            (user, None) => Saved::User(user).into(),
+        };
 
         let paths: Paths = cc
             .storage
             .and_then(|s| eframe::get_value(s, "paths"))
             .unwrap_or_default();
 
-        cc.egui_ctx.set_style(theme.style());
+        let uids: Uids = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, "uids"))
+            .unwrap_or_default();
 
-        let (message_tx, message_rx) = mpsc::channel();
+        let auto_save_dir: Option<PathBuf> = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, "auto_save_dir"))
+            .unwrap_or_default();
 
-        update(&message_tx);
+        let hooks: String = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, "hooks"))
+            .unwrap_or_default();
 
-        if let Some(user) = &user {
-            let message_tx = message_tx.clone();
-            let id = user.id.clone();
+        let webhook: webhook::Webhook = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, "webhook"))
+            .unwrap_or_default();
 
-            thread::spawn(move || {
-                let Some(mut response) = ureq::post("https://stardb.gg/api/users/auth/renew")
-                    .header("Cookie", &id)
-                    .send_empty()
+        let server_enabled: bool = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, "server"))
+            .unwrap_or_default();
+        let server = server_enabled
+            .then(|| server::Server::start(server::DEFAULT_PORT))
+            .and_then(|server| {
+                server
+                    .map_err(|e| tracing::warn!("Couldn't start the server: {e}"))
                     .ok()
-                    .and_then(|r| (r.status() == 200).then_some(r))
-                else {
-                    message_tx
-                        .send(Message::GoTo(State::Error(
-                            "There was an error renewing your account cookie".to_string(),
-                        )))
-                        .unwrap();
-                    message_tx.send(Message::User(None)).unwrap();
-                    return;
-                };
-
-                let id = response
-                    .headers()
-                    .get("Set-Cookie")
-                    .unwrap()
-                    .to_str()
-                    .unwrap()
-                    .split(';')
-                    .next()
-                    .unwrap()
-                    .to_string();
-                let username = response.body_mut().read_json().unwrap();
-
-                let user = User { id, username };
-                message_tx.send(Message::User(Some(user))).unwrap();
             });
+
+        cc.egui_ctx.set_style(theme.style());
+
+        let (message_tx, message_rx) = mpsc::channel();
+
+        update(&message_tx);
+        tasks::load_tables(&message_tx);
+        tasks::tick_sync_retries(&message_tx);
+
+        let renewing = accounts.users.iter().map(|u| u.username.clone()).collect();
+        for user in &accounts.users {
+            renew(user, &message_tx);
         }
 
         Self {
@@ -149,16 +396,43 @@ impl App {
             game: games::Game::Hsr,
             toasts: egui_notify::Toasts::default().with_anchor(egui_notify::Anchor::BottomRight),
             theme,
-            user,
+            language,
+            weights,
+            accounts,
+            vault,
+            passphrase: None,
+            session_locked,
             paths,
+            uids,
+            auto_save_dir,
+            hooks,
+            webhook,
+            server,
+            events,
+            tables: None,
+            sync_preview: None,
+            pending_syncs: 0,
+            renewing,
+            retrying_syncs: false,
+            retried_syncs: false,
             account_popup_open: false,
             theme_popup_open: false,
+            language_popup_open: false,
         }
     }
 
     fn message(&mut self, message: Message) {
         match message {
             Message::GoTo(state) => {
+                self.auto_save(&state);
+                self.publish(&state);
+                self.emit(&state);
+                self.push(&state);
+                self.sync_preview = None;
+                self.state = state;
+            }
+            Message::Restored(state) => {
+                self.sync_preview = None;
                 self.state = state;
             }
             Message::Game(game) => {
@@ -181,38 +455,399 @@ impl App {
                 }
             }
             Message::Theme(theme) => self.theme = theme,
-            Message::User(user) => self.user = user,
+            Message::Language(language) => self.language = language,
+            Message::Weights(weights) => self.weights = weights,
+            // Syncs queued for the account waited for it, they run right away.
+            Message::User(user) => {
+                self.accounts.active = Some(user.username.clone());
+                self.accounts.upsert(user);
+                self.save_session();
+                self.retried_syncs = false;
+                self.retry_syncs();
+            }
+            Message::Renewed(user) => {
+                self.renewing.remove(&user.username);
+                self.accounts.upsert(user);
+                self.save_session();
+                self.retry_syncs();
+            }
+            Message::Expired(username) => {
+                self.renewing.remove(&username);
+                self.accounts.remove(&username);
+                self.save_session();
+                self.toasts.add(egui_notify::Toast::error(format!(
+                    "The stardb session of {username} expired, please log in again"
+                )));
+                self.retry_syncs();
+            }
+            Message::NotRenewed(username, e) => {
+                self.toasts.add(egui_notify::Toast::warning(format!(
+                    "Couldn't renew the cookie of {username}: {e}"
+                )));
+                self.renewing.remove(&username);
+                self.retry_syncs();
+            }
+            Message::SwitchAccount(username) => {
+                if self.accounts.get(&username).is_some() {
+                    self.accounts.active = Some(username);
+                    self.sync_preview = None;
+                    self.save_session();
+                }
+            }
+            Message::Bind(username) => {
+                let Some(uid) = self.uids.get(self.game) else {
+                    return;
+                };
+
+                let key = binding_key(self.game, &uid);
+                match username {
+                    Some(username) => self.accounts.bindings.insert(key, username),
+                    None => self.accounts.bindings.remove(&key),
+                };
+                self.sync_preview = None;
+                self.save_session();
+            }
+            // Only fills in what the user hasn't set themselves.
+            Message::DetectedUid(game, uid, username) => {
+                if let Some(username) = username.or_else(|| self.accounts.active.clone()) {
+                    self.accounts
+                        .bindings
+                        .entry(binding_key(game, &uid))
+                        .or_insert(username);
+                    self.save_session();
+                }
+
+                let saved = match game {
+                    games::Game::Hsr => &mut self.uids.hsr,
+                    games::Game::Gi => &mut self.uids.gi,
+                    games::Game::Zzz => &mut self.uids.zzz,
+                };
+                if saved.as_deref().is_none_or(str::is_empty) {
+                    *saved = Some(uid);
+                }
+            }
+            Message::Unlock(passphrase) => {
+                let Some(vault) = &self.vault else {
+                    return;
+                };
+
+                match vault.load::<Saved>(Some(&passphrase)) {
+                    Ok(saved) => {
+                        let mut accounts: Accounts = saved.map(Accounts::from).unwrap_or_default();
+                        for user in &accounts.users {
+                            if self.accounts.get(&user.username).is_none() {
+                                self.renewing.insert(user.username.clone());
+                                renew(user, &self.message_tx);
+                            }
+                        }
+                        accounts.merge(std::mem::take(&mut self.accounts));
+
+                        self.accounts = accounts;
+                        self.passphrase = Some(passphrase);
+                        self.session_locked = false;
+                        self.save_session();
+                        self.retry_syncs();
+                    }
+                    Err(e) => {
+                        self.toasts.add(egui_notify::Toast::error(e.to_string()));
+                    }
+                }
+            }
+            Message::Passphrase(passphrase) => {
+                self.passphrase = passphrase.filter(|p| !p.is_empty());
+                self.save_session();
+                self.toasts
+                    .add(egui_notify::Toast::success(match self.passphrase {
+                        Some(_) => "Session protected by the passphrase",
+                        None => "Passphrase removed",
+                    }));
+            }
+            Message::ForgetEverything => {
+                for user in std::mem::take(&mut self.accounts).users {
+                    logout(user);
+                }
+                self.session_locked = false;
+                self.passphrase = None;
+                self.sync_preview = None;
+                self.pending_syncs = 0;
+                if let Some(server) = &self.server {
+                    server.forget();
+                }
+                if matches!(self.state, State::Pulls(_) | State::Snapshots(_)) {
+                    self.state = State::Menu;
+                }
+
+                let toast = match tasks::forget_everything(self.vault.as_ref()) {
+                    Ok(()) => {
+                        egui_notify::Toast::success("Forgot the session, pull urls and snapshots")
+                    }
+                    Err(e) => egui_notify::Toast::error(format!("Couldn't forget everything: {e}")),
+                };
+                self.toasts.add(toast);
+            }
             Message::Path(path) => match self.game {
                 games::Game::Hsr => self.paths.hsr = Some(path),
                 games::Game::Gi => self.paths.gi = Some(path),
                 games::Game::Zzz => self.paths.zzz = Some(path),
             },
+            // Also used as a folder name, so only digits are kept.
+            Message::Uid(game, mut uid) => {
+                uid.retain(|c| c.is_ascii_digit());
+                match game {
+                    games::Game::Hsr => self.uids.hsr = Some(uid),
+                    games::Game::Gi => self.uids.gi = Some(uid),
+                    games::Game::Zzz => self.uids.zzz = Some(uid),
+                }
+            }
+            Message::AutoSaveDir(dir) => self.auto_save_dir = dir,
+            Message::Hooks(hooks) => self.hooks = hooks,
+            Message::Webhook(webhook) => self.webhook = webhook,
+            Message::SyncPreview(diff) => self.sync_preview = diff,
+            Message::PendingSyncs(pending) => self.pending_syncs = pending,
+            Message::RetrySyncs => self.retry_syncs(),
+            Message::SyncsRetried(pending) => {
+                if let Some(pending) = pending {
+                    self.pending_syncs = pending;
+                }
+                self.retrying_syncs = false;
+            }
+            Message::Server(enabled) => {
+                self.server = None;
+                if enabled {
+                    match server::Server::start(server::DEFAULT_PORT) {
+                        Ok(server) => self.server = Some(server),
+                        Err(e) => {
+                            self.toasts.add(egui_notify::Toast::error(format!(
+                                "Couldn't start the server: {e}"
+                            )));
+                        }
+                    }
+                }
+            }
+            // Logs out of the active account, the next saved one takes over.
             Message::Logout => {
-                let Some(user) = &self.user else {
+                let Some(user) = self
+                    .accounts
+                    .active
+                    .clone()
+                    .and_then(|username| self.accounts.remove(&username))
+                else {
                     return;
                 };
 
-                let id = user.id.clone();
-                self.user = None;
+                self.sync_preview = None;
+                self.save_session();
+                logout(user);
+            }
+            Message::Progress(progress) => {
+                if let Some(events) = &self.events {
+                    events.emit(progress.into());
+                }
 
-                thread::spawn(move || {
-                    let _ = ureq::post("https://stardb.gg/api/users/auth/logout")
-                        .header("Cookie", &id)
-                        .send_empty();
-                });
+                let mut toast = match progress {
+                    games::Progress::DeviceReady(i) => {
+                        self.message(Message::GoTo(State::Waiting("Running".to_string())));
+                        egui_notify::Toast::success(format!("Device {i} Ready~!"))
+                    }
+                    games::Progress::DeviceRestarting(i) => {
+                        egui_notify::Toast::error(format!("Device {i} Error. Starting up again..."))
+                    }
+                    games::Progress::Handshake | games::Progress::CommandMatched(_) => return,
+                };
+                toast.duration(None);
+                self.toasts.add(toast);
             }
             Message::Toast(toast) => {
                 self.toasts.add(toast);
             }
+            Message::Tables(tables) => self.tables = Some(tables),
+        }
+    }
+}
+
+impl App {
+    // Keeps the vault in sync with the account, unless it is still locked.
+    fn save_session(&self) {
+        let Some(vault) = &self.vault else {
+            return;
+        };
+        if self.session_locked {
+            return;
+        }
+
+        if let Err(e) = vault.save(&self.accounts, self.passphrase.as_deref()) {
+            tracing::warn!("Couldn't save the session: {e}");
+        }
+    }
+
+    // Runs the due syncs with the cookies the accounts have now. Waits for the
+    // vault and for renewals, so that no job runs with a superseded cookie.
+    fn retry_syncs(&mut self) {
+        if self.retrying_syncs || self.session_locked || !self.renewing.is_empty() {
+            return;
+        }
+
+        let cookies = self
+            .accounts
+            .users
+            .iter()
+            .map(|u| (u.username.clone(), u.id.clone()))
+            .collect();
+        tasks::retry_syncs(cookies, !self.retried_syncs, &self.message_tx);
+        self.retrying_syncs = true;
+        self.retried_syncs = true;
+    }
+
+    // Writes every result to the auto-save dir as soon as it is shown.
+    fn auto_save(&mut self, state: &State) {
+        let Some(dir) = &self.auto_save_dir else {
+            return;
+        };
+
+        let (kind, extension, bytes) = match state {
+            State::Achievements(achievements, _) => (
+                "achievements",
+                "json",
+                export::stardb_achievements(self.game, achievements),
+            ),
+            // Kept like captured, the problems are pointed out below.
+            State::Artifacts(capture) => {
+                ("artifacts", "json", export::good(&capture.artifacts, true))
+            }
+            State::Pulls(url) => ("pulls", "txt", Ok(url.as_bytes().to_vec())),
+            _ => return,
+        };
+
+        let uid = self.uids.get(self.game);
+        let file_name = export::file_name(
+            self.game,
+            kind,
+            uid.as_deref(),
+            crate::snapshots::now(),
+            extension,
+        );
+        match bytes.and_then(|bytes| export::auto_save(dir, &file_name, &bytes)) {
+            Ok(path) => {
+                tracing::info!("Auto-saved {}", path.display());
+                if let State::Artifacts(capture) = state
+                    && !capture.problems.is_empty()
+                {
+                    self.toasts.add(egui_notify::Toast::warning(format!(
+                        "Auto-saved {file_name}, {} artifacts failed validation",
+                        capture.problems.len()
+                    )));
+                }
+                tasks::run_hooks(
+                    self.hooks.clone(),
+                    path,
+                    self.game,
+                    kind.to_string(),
+                    uid,
+                    &self.message_tx,
+                );
+            }
+            Err(e) => {
+                self.toasts.add(egui_notify::Toast::error(format!(
+                    "Error auto-saving {file_name}: {e}"
+                )));
+            }
+        }
+    }
+
+    // The export of a result screen as JSON, with its kind.
+    fn result(&self, state: &State) -> Option<(&'static str, serde_json::Value)> {
+        let json = |bytes: anyhow::Result<Vec<u8>>| {
+            bytes
+                .and_then(|bytes| Ok(serde_json::from_slice(&bytes)?))
+                .unwrap_or_default()
+        };
+
+        match state {
+            State::Achievements(achievements, _) => Some((
+                "achievements",
+                json(export::stardb_achievements(self.game, achievements)),
+            )),
+            State::Artifacts(capture) => {
+                Some(("artifacts", json(export::good(&capture.artifacts, true))))
+            }
+            State::Pulls(url) => Some(("pulls", serde_json::Value::from(url.as_str()))),
+            _ => None,
+        }
+    }
+
+    // Mirrors the screens that matter to scripts as NDJSON events.
+    fn emit(&self, state: &State) {
+        let Some(events) = &self.events else {
+            return;
+        };
+
+        let event = match state {
+            State::Waiting(status) => events::Event::Waiting {
+                status: status.clone(),
+            },
+            State::Error(message) => events::Event::Error {
+                message: message.clone(),
+            },
+            state => {
+                let Some((kind, data)) = self.result(state) else {
+                    return;
+                };
+                events::Event::Result {
+                    game: self.game.id(),
+                    kind,
+                    data,
+                }
+            }
+        };
+
+        events.emit(event);
+    }
+
+    fn push(&self, state: &State) {
+        if !self.webhook.is_enabled() {
+            return;
+        }
+
+        if let Some((kind, data)) = self.result(state) {
+            tasks::push_webhook(
+                self.webhook.clone(),
+                self.game,
+                kind,
+                self.uids.get(self.game),
+                data,
+                &self.message_tx,
+            );
+        }
+    }
+
+    // Tells companion tools about every result.
+    fn publish(&self, state: &State) {
+        let Some(server) = &self.server else {
+            return;
+        };
+
+        match state {
+            State::Achievements(..) => server.publish(self.game, server::Kind::Achievements, None),
+            State::Artifacts(..) => server.publish(self.game, server::Kind::Artifacts, None),
+            State::Pulls(url) => server.publish(self.game, server::Kind::Pulls, Some(url)),
+            _ => {}
         }
     }
 }
 
 impl eframe::App for App {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, "user", &self.user);
+        // The session lives in the vault, this clears what older versions wrote.
+        eframe::set_value(storage, "user", &None::<User>);
         eframe::set_value(storage, "theme", &self.theme);
+        eframe::set_value(storage, "language", &self.language);
+        eframe::set_value(storage, "weights", &self.weights);
         eframe::set_value(storage, "paths", &self.paths);
+        eframe::set_value(storage, "uids", &self.uids);
+        eframe::set_value(storage, "auto_save_dir", &self.auto_save_dir);
+        eframe::set_value(storage, "hooks", &self.hooks);
+        eframe::set_value(storage, "webhook", &self.webhook);
+        eframe::set_value(storage, "server", &self.server.is_some());
     }
 
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
@@ -240,12 +875,15 @@ impl eframe::App for App {
                 }
                 State::Login(username, password) => ui::login::show(ui, username, password, self),
                 State::Menu => ui::menu::show(ui, self),
-                State::Achievements(achievements) => ui::achievements::show(ui, achievements, self),
-                State::Artifacts(artifacts) => ui::artifacts::show(ui, artifacts, self),
+                State::Achievements(achievements, names) => {
+                    ui::achievements::show(ui, achievements, names, self)
+                }
+                State::Artifacts(capture) => ui::artifacts::show(ui, capture, self),
                 State::Error(s) => ui::error::show(ui, s),
                 State::Game => ui::game::show(ui, self),
                 State::Pulls(url) => ui::pulls::show(ui, url, self),
                 State::PullMenu => ui::pull_menu::show(ui, self),
+                State::Snapshots(report) => ui::snapshots::show(ui, report, self),
             }
         });
 
@@ -253,6 +891,42 @@ impl eframe::App for App {
     }
 }
 
+// Swaps the saved cookie for a fresh one, dropping the account once it expired.
+fn renew(user: &User, message_tx: &mpsc::Sender<Message>) {
+    let message_tx = message_tx.clone();
+    let id = user.id.clone();
+    let username = user.username.clone();
+
+    thread::spawn(move || {
+        match stardb::Client::default().renew(&id) {
+            Ok(session) => {
+                let user = User {
+                    id: session.cookie,
+                    username: session.username,
+                };
+                message_tx.send(Message::Renewed(user)).unwrap();
+            }
+            Err(stardb::Error::AuthExpired) => {
+                message_tx.send(Message::Expired(username)).unwrap();
+            }
+            // Keeps the account, the cookie may still be good once stardb is reachable.
+            Err(e) => {
+                message_tx
+                    .send(Message::NotRenewed(username, e.to_string()))
+                    .unwrap();
+            }
+        }
+    });
+}
+
+fn logout(user: User) {
+    thread::spawn(move || {
+        if let Err(e) = stardb::Client::default().logout(&user.id) {
+            tracing::warn!("Couldn't log out of {}: {e}", user.username);
+        }
+    });
+}
+
 #[cfg(not(debug_assertions))]
 fn update(message_tx: &mpsc::Sender<Message>) {
     let message_tx = message_tx.clone();
@@ -276,3 +950,109 @@ fn update(message_tx: &mpsc::Sender<Message>) {
 fn update(message_tx: &mpsc::Sender<Message>) {
     message_tx.send(Message::GoTo(State::Menu)).unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(username: &str) -> User {
+        User {
+            id: format!("id={username}"),
+            username: username.to_string(),
+        }
+    }
+
+    fn synced_to(accounts: &Accounts, game: Game, uid: Option<&str>) -> Option<String> {
+        accounts.for_uid(game, uid).map(|u| u.username.clone())
+    }
+
+    #[test]
+    fn bound_uids_sync_to_their_account() {
+        let mut accounts = Accounts {
+            users: vec![user("kiana"), user("mei")],
+            active: Some("kiana".to_string()),
+            ..Default::default()
+        };
+        accounts
+            .bindings
+            .insert(binding_key(Game::Hsr, "100"), "mei".to_string());
+
+        assert_eq!(
+            synced_to(&accounts, Game::Hsr, Some("100")).as_deref(),
+            Some("mei")
+        );
+        assert_eq!(
+            synced_to(&accounts, Game::Hsr, Some("200")).as_deref(),
+            Some("kiana")
+        );
+        assert_eq!(
+            synced_to(&accounts, Game::Hsr, None).as_deref(),
+            Some("kiana")
+        );
+        assert_eq!(
+            synced_to(&accounts, Game::Gi, Some("100")).as_deref(),
+            Some("kiana")
+        );
+
+        // Nothing syncs to kiana's account until mei logs back in.
+        accounts.remove("mei");
+        assert_eq!(synced_to(&accounts, Game::Hsr, Some("100")), None);
+        assert_eq!(accounts.binding(Game::Hsr, "100"), Some("mei"));
+        assert_eq!(
+            synced_to(&accounts, Game::Hsr, Some("200")).as_deref(),
+            Some("kiana")
+        );
+
+        accounts.upsert(user("mei"));
+        assert_eq!(
+            synced_to(&accounts, Game::Hsr, Some("100")).as_deref(),
+            Some("mei")
+        );
+
+        accounts.remove("mei");
+        accounts.remove("kiana");
+        assert!(accounts.active.is_none());
+        assert_eq!(synced_to(&accounts, Game::Hsr, Some("200")), None);
+    }
+
+    #[test]
+    fn keeps_logins_made_while_locked() {
+        let mut saved = Accounts {
+            users: vec![user("kiana"), user("mei")],
+            active: Some("kiana".to_string()),
+            ..Default::default()
+        };
+        saved
+            .bindings
+            .insert(binding_key(Game::Hsr, "100"), "mei".to_string());
+
+        let mut locked = Accounts {
+            users: vec![User {
+                id: "id=new".to_string(),
+                username: "mei".to_string(),
+            }],
+            active: Some("mei".to_string()),
+            ..Default::default()
+        };
+        locked
+            .bindings
+            .insert(binding_key(Game::Gi, "700"), "mei".to_string());
+
+        saved.merge(locked);
+        assert_eq!(saved.users.len(), 2);
+        assert_eq!(saved.get("mei").unwrap().id, "id=new");
+        assert_eq!(saved.active.as_deref(), Some("mei"));
+        assert_eq!(saved.bindings.len(), 2);
+    }
+
+    #[test]
+    fn loads_the_single_user_session() {
+        let saved: Saved = serde_json::from_str(r#"{"id":"id=abc","username":"kiana"}"#).unwrap();
+        let accounts = Accounts::from(saved);
+        assert_eq!(accounts.active.as_deref(), Some("kiana"));
+        assert_eq!(accounts.users.len(), 1);
+
+        let saved: Saved = serde_json::from_str("null").unwrap();
+        assert!(Accounts::from(saved).users.is_empty());
+    }
+}
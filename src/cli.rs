@@ -0,0 +1,306 @@
+use std::{io::Write, path::PathBuf, sync::Arc};
+
+use crate::{
+    data::{Language, Tables},
+    events::{Event, EventLog},
+    export,
+    games::{Artifacts, Game, Progress, Session, validate},
+    stardb,
+};
+
+const USAGE: &str = "Usage:
+  stardb-exporter gi artifacts [--out <file>] [--min-level <level>]
+  stardb-exporter <gi|hsr> achievements [--out <file>] [--sync] [--mirror]
+  stardb-exporter <gi|hsr|zzz> pulls [--path <game data dir>] [--out <file>]
+
+Results are written to stdout unless --out is given, progress goes to stderr.
+--events <file> appends NDJSON events (device ready, handshake, command
+matched, result, error) to a file, or to stdout instead of the result for -.
+--sync adds the missing achievements to the stardb account whose session
+cookie is in the STARDB_COOKIE environment variable. --mirror syncs and also
+removes the ones that weren't captured.";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Kind {
+    Achievements,
+    Artifacts,
+    Pulls,
+}
+
+impl Kind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Kind::Achievements => "achievements",
+            Kind::Artifacts => "artifacts",
+            Kind::Pulls => "pulls",
+        }
+    }
+}
+
+struct Command {
+    game: Game,
+    kind: Kind,
+    out: Option<PathBuf>,
+    min_level: u32,
+    sync: Option<stardb::SyncMode>,
+    path: Option<PathBuf>,
+    events: Option<String>,
+}
+
+pub fn is_command(arg: &str) -> bool {
+    matches!(arg, "gi" | "hsr" | "zzz")
+}
+
+fn parse(args: &[String]) -> anyhow::Result<Command> {
+    let usage = || anyhow::anyhow!(USAGE);
+
+    let [game, kind, options @ ..] = args else {
+        return Err(usage());
+    };
+
+    let game = match game.as_str() {
+        "gi" => Game::Gi,
+        "hsr" => Game::Hsr,
+        "zzz" => Game::Zzz,
+        _ => return Err(usage()),
+    };
+
+    let kind = match (kind.as_str(), game) {
+        ("artifacts", Game::Gi) => Kind::Artifacts,
+        ("achievements", Game::Gi | Game::Hsr) => Kind::Achievements,
+        ("pulls", _) => Kind::Pulls,
+        (kind, game) => {
+            return Err(anyhow::anyhow!(
+                "{} has no {kind} exporter\n\n{USAGE}",
+                game.id()
+            ));
+        }
+    };
+
+    let mut command = Command {
+        game,
+        kind,
+        out: None,
+        min_level: 0,
+        sync: None,
+        path: None,
+        events: None,
+    };
+
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let mut value = || options.next().cloned().ok_or_else(usage);
+
+        match (option.as_str(), kind) {
+            ("--out", _) => command.out = Some(PathBuf::from(value()?)),
+            ("--events", _) => command.events = Some(value()?),
+            ("--min-level", Kind::Artifacts) => {
+                command.min_level = value()?
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("--min-level expects a number"))?;
+            }
+            ("--sync", Kind::Achievements) => {
+                command.sync.get_or_insert(stardb::SyncMode::AddOnly);
+            }
+            ("--mirror", Kind::Achievements) => command.sync = Some(stardb::SyncMode::Mirror),
+            ("--path", Kind::Pulls) => command.path = Some(PathBuf::from(value()?)),
+            (option, _) => {
+                return Err(anyhow::anyhow!("Unexpected argument {option}\n\n{USAGE}"));
+            }
+        }
+    }
+
+    Ok(command)
+}
+
+pub fn run(args: &[String]) -> anyhow::Result<()> {
+    attach_console();
+
+    let command = parse(args)?;
+    let events = command.events.as_deref().map(EventLog::open).transpose()?;
+
+    let result = export(&command, events.as_ref());
+    if let (Err(e), Some(events)) = (&result, &events) {
+        events.emit(Event::Error {
+            message: e.to_string(),
+        });
+    }
+
+    result
+}
+
+fn export(command: &Command, events: Option<&EventLog>) -> anyhow::Result<()> {
+    let game = command.game;
+    let progress = |progress: Progress| {
+        match progress {
+            Progress::DeviceReady(i) => eprintln!("Device {i} ready"),
+            Progress::DeviceRestarting(i) => eprintln!("Device {i} error, starting up again"),
+            Progress::Handshake => eprintln!("Connected to the game"),
+            Progress::CommandMatched(command) => eprintln!("Reading {command}"),
+        }
+        if let Some(events) = events {
+            events.emit(progress.into());
+        }
+    };
+
+    let (bytes, data) = match command.kind {
+        Kind::Achievements => {
+            let sync = match command.sync {
+                Some(mode) => Some((
+                    mode,
+                    // Not an argument, those show up in process listings.
+                    std::env::var("STARDB_COOKIE")
+                        .map_err(|_| anyhow::anyhow!("--sync needs STARDB_COOKIE"))?,
+                )),
+                None => None,
+            };
+
+            eprintln!("Waiting for achievements, log into the game now");
+            let achievements = Session::achievements(game, Language::default())
+                .wait(progress)?
+                .completed;
+            eprintln!("Found {} achievements", achievements.len());
+
+            if let Some((mode, cookie)) = sync {
+                let diff = stardb::Client::default().sync_achievements(
+                    &cookie,
+                    game,
+                    &achievements,
+                    mode,
+                )?;
+                let removed = match mode {
+                    stardb::SyncMode::AddOnly => 0,
+                    stardb::SyncMode::Mirror => diff.to_remove.len(),
+                };
+                eprintln!(
+                    "Synced to stardb: {} added, {removed} removed, {} unchanged",
+                    diff.to_add.len(),
+                    diff.unchanged.len()
+                );
+            }
+
+            let bytes = export::stardb_achievements(game, &achievements)?;
+            let data = serde_json::from_slice(&bytes)?;
+            (bytes, data)
+        }
+        Kind::Artifacts => {
+            let tables = Arc::new(Tables::load(&crate::storage_dir()?)?);
+
+            eprintln!("Waiting for artifacts, log into the game now");
+            let Artifacts {
+                mut artifacts,
+                warnings,
+            } = Session::artifacts(game, tables).wait(progress)?;
+            for warning in &warnings {
+                eprintln!("{warning}");
+            }
+
+            let found = artifacts.len();
+            artifacts.retain(|a| a.level >= command.min_level);
+            eprintln!(
+                "Found {found} artifacts, exporting {} at level {} or above",
+                artifacts.len(),
+                command.min_level
+            );
+
+            for (i, problems) in validate::validate_all(&artifacts) {
+                eprintln!("Artifact {i} failed validation: {}", problems.join(", "));
+            }
+
+            let bytes = export::good(&artifacts, true)?;
+            let data = serde_json::from_slice(&bytes)?;
+            (bytes, data)
+        }
+        Kind::Pulls => {
+            eprintln!("Looking for the pull url");
+
+            let url = game.locate_gacha_url(command.path.as_deref())?;
+            (
+                format!("{url}\n").into_bytes(),
+                serde_json::Value::from(url),
+            )
+        }
+    };
+
+    if let Some(events) = events {
+        events.emit(Event::Result {
+            game: game.id(),
+            kind: command.kind.as_str(),
+            data,
+        });
+    }
+
+    match &command.out {
+        Some(out) => {
+            std::fs::write(out, bytes)?;
+            eprintln!("Wrote {}", out.display());
+        }
+        // The result event already has the data, and stdout has to stay NDJSON.
+        None if events.is_some_and(EventLog::is_stdout) => {}
+        None => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(&bytes)?;
+            stdout.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+// The binary uses the windows subsystem, so it has no console of its own and
+// output would be lost when started from a terminal.
+#[cfg(windows)]
+pub fn attach_console() {
+    unsafe extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn attach_console() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_exporter_options() {
+        let command = parse(&args("gi artifacts --out good.json --min-level 16")).unwrap();
+        assert!(command.game == Game::Gi);
+        assert_eq!(command.kind, Kind::Artifacts);
+        assert_eq!(command.out, Some(PathBuf::from("good.json")));
+        assert_eq!(command.min_level, 16);
+
+        let command = parse(&args("hsr achievements --sync")).unwrap();
+        assert_eq!(command.kind, Kind::Achievements);
+        assert_eq!(command.sync, Some(stardb::SyncMode::AddOnly));
+
+        let command = parse(&args("gi achievements --mirror --sync")).unwrap();
+        assert_eq!(command.sync, Some(stardb::SyncMode::Mirror));
+
+        let command = parse(&args("zzz pulls --path D:/ZenlessZoneZero_Data --events -")).unwrap();
+        assert_eq!(command.kind, Kind::Pulls);
+        assert_eq!(command.events.as_deref(), Some("-"));
+        assert_eq!(command.path, Some(PathBuf::from("D:/ZenlessZoneZero_Data")));
+    }
+
+    #[test]
+    fn rejects_unsupported_combinations() {
+        assert!(parse(&args("hsr artifacts")).is_err());
+        assert!(parse(&args("zzz achievements")).is_err());
+        assert!(parse(&args("gi pulls --min-level 16")).is_err());
+        assert!(parse(&args("gi artifacts --min-level")).is_err());
+        assert!(parse(&args("gi")).is_err());
+        assert!(parse(&args("hsr achievements --sync --cookie secret")).is_err());
+    }
+}
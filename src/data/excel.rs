@@ -0,0 +1,220 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use crate::games::{Affix, SlotKey, StatKey};
+
+use super::{ArtifactData, Language, Names, Tables};
+
+#[derive(serde::Deserialize)]
+#[allow(non_snake_case)]
+struct ReliquaryExcelConfigDataEntry {
+    equipType: String,
+    id: u32,
+    rankLevel: u32,
+    setId: u32,
+}
+
+#[derive(serde::Deserialize)]
+#[allow(non_snake_case)]
+struct DisplayItemExcelConfigDataEntry {
+    displayType: String,
+    nameTextMapHash: u32,
+    param: u32,
+}
+
+#[derive(serde::Deserialize)]
+#[allow(non_snake_case)]
+struct ReliquaryMainPropExcelConfigDataEntry {
+    id: u32,
+    propType: String,
+}
+
+#[derive(serde::Deserialize)]
+#[allow(non_snake_case)]
+struct ReliquaryAffixExcelConfigDataEntry {
+    id: u32,
+    propType: String,
+    propValue: f32,
+}
+
+#[derive(serde::Deserialize)]
+#[allow(non_snake_case)]
+struct ManualTextMapConfigDataEntry {
+    textMapId: String,
+    textMapContentTextMapHash: u32,
+}
+
+// Accepts either a flat folder or the root of a dump with `ExcelBinOutput`
+// and `TextMap` subfolders.
+fn find(dir: &Path, file: &str) -> Option<PathBuf> {
+    [
+        dir.to_path_buf(),
+        dir.join("ExcelBinOutput"),
+        dir.join("TextMap"),
+    ]
+    .into_iter()
+    .map(|d| d.join(file))
+    .find(|p| p.exists())
+}
+
+fn read(dir: &Path, file: &str) -> anyhow::Result<String> {
+    let path = find(dir, file)
+        .ok_or_else(|| anyhow::anyhow!("Couldn't find {file} in {}", dir.display()))?;
+    Ok(std::fs::read_to_string(path)?)
+}
+
+pub fn read_dir(dir: &Path) -> anyhow::Result<Tables> {
+    let version = match std::fs::read_to_string(dir.join("version.txt")) {
+        Ok(version) => version.trim().to_string(),
+        Err(_) => dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    };
+
+    Ok(Tables {
+        format: super::FORMAT,
+        version,
+        names: names(dir)?,
+        artifacts: artifacts(&read(dir, "ReliquaryExcelConfigData.json")?)?,
+        main_props: main_props(&read(dir, "ReliquaryMainPropExcelConfigData.json")?)?,
+        affixes: affixes(&read(dir, "ReliquaryAffixExcelConfigData.json")?)?,
+        source: Default::default(),
+        set_keys: Default::default(),
+    })
+}
+
+// TextMaps are tens of megabytes each, so they are read one at a time.
+fn names(dir: &Path) -> anyhow::Result<BTreeMap<Language, Names>> {
+    let display_item_excel_config_data: Vec<DisplayItemExcelConfigDataEntry> =
+        serde_json::from_str(&read(dir, "DisplayItemExcelConfigData.json")?)?;
+
+    // Map setId -> nameTextMapHash
+    let mut setid_to_hash = BTreeMap::new();
+    for entry in display_item_excel_config_data {
+        if entry.displayType == "RELIQUARY_ITEM" {
+            setid_to_hash.insert(entry.param, entry.nameTextMapHash.to_string());
+        }
+    }
+
+    // Map StatKey -> textMapContentTextMapHash
+    let mut stat_to_hash = BTreeMap::new();
+    match read(dir, "ManualTextMapConfigData.json") {
+        Ok(content) => {
+            let manual_text_map_config_data: Vec<ManualTextMapConfigDataEntry> =
+                serde_json::from_str(&content)?;
+            for entry in manual_text_map_config_data {
+                if let Some(key) = StatKey::from_fight_prop(&entry.textMapId) {
+                    stat_to_hash.insert(key, entry.textMapContentTextMapHash.to_string());
+                }
+            }
+        }
+        Err(e) => tracing::warn!("No stat names: {e}"),
+    }
+
+    let mut result = BTreeMap::new();
+    for language in Language::ALL {
+        let file = format!("TextMap{}.json", language.text_map());
+        let content = match read(dir, &file) {
+            Ok(content) => content,
+            Err(e) if language == Language::En => return Err(e),
+            Err(e) => {
+                tracing::warn!("No {language:?} names: {e}");
+                continue;
+            }
+        };
+        let text_map: HashMap<String, String> = serde_json::from_str(&content)?;
+
+        let names = Names {
+            sets: setid_to_hash
+                .iter()
+                .filter_map(|(id, hash)| Some((*id, text_map.get(hash)?.clone())))
+                .collect(),
+            stats: stat_to_hash
+                .iter()
+                .filter_map(|(key, hash)| Some((*key, text_map.get(hash)?.clone())))
+                .collect(),
+        };
+        result.insert(language, names);
+    }
+
+    Ok(result)
+}
+
+fn artifacts(content: &str) -> anyhow::Result<BTreeMap<u32, ArtifactData>> {
+    let reliquary_excel_config_data: Vec<ReliquaryExcelConfigDataEntry> =
+        serde_json::from_str(content)?;
+
+    let mut result = BTreeMap::new();
+    for entry in reliquary_excel_config_data {
+        let Some(slot_key) = SlotKey::from_equip_type(&entry.equipType) else {
+            tracing::debug!("ID {}: unknown equip type {}", entry.id, entry.equipType);
+            continue;
+        };
+
+        result.insert(
+            entry.id,
+            ArtifactData {
+                setId: entry.setId,
+                slotKey: slot_key,
+                rarity: entry.rankLevel,
+            },
+        );
+    }
+
+    for (id, data) in &result {
+        tracing::trace!("ID {} => {:?}", id, data);
+    }
+
+    Ok(result)
+}
+
+fn main_props(content: &str) -> anyhow::Result<BTreeMap<u32, StatKey>> {
+    let reliquary_main_prop_excel_config: Vec<ReliquaryMainPropExcelConfigDataEntry> =
+        serde_json::from_str(content)?;
+
+    let mut result = BTreeMap::new();
+    for entry in reliquary_main_prop_excel_config {
+        let Some(key) = StatKey::from_fight_prop(&entry.propType) else {
+            tracing::debug!("ID {}: unknown prop type {}", entry.id, entry.propType);
+            continue;
+        };
+
+        result.insert(entry.id, key);
+    }
+
+    for (id, data) in &result {
+        tracing::trace!("ID {} => {:?}", id, data);
+    }
+
+    Ok(result)
+}
+
+fn affixes(content: &str) -> anyhow::Result<BTreeMap<u32, Affix>> {
+    let reliquary_affix_excel_config: Vec<ReliquaryAffixExcelConfigDataEntry> =
+        serde_json::from_str(content)?;
+
+    let mut result = BTreeMap::new();
+    for entry in reliquary_affix_excel_config {
+        let Some(key) = StatKey::from_fight_prop(&entry.propType) else {
+            tracing::debug!("ID {}: unknown prop type {}", entry.id, entry.propType);
+            continue;
+        };
+
+        result.insert(
+            entry.id,
+            Affix {
+                key,
+                value: entry.propValue,
+            },
+        );
+    }
+
+    for (id, data) in &result {
+        tracing::trace!("ID {} => {:?}", id, data);
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,88 @@
+// Only languages whose script is covered by the bundled Inter font. The
+// Chinese, Japanese, Korean and Thai TextMaps would render as missing glyphs.
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Language {
+    #[default]
+    En,
+    De,
+    Es,
+    Fr,
+    Id,
+    It,
+    Pt,
+    Ru,
+    Tr,
+    Vi,
+}
+
+impl Language {
+    pub const ALL: [Language; 10] = [
+        Language::En,
+        Language::De,
+        Language::Es,
+        Language::Fr,
+        Language::Id,
+        Language::It,
+        Language::Pt,
+        Language::Ru,
+        Language::Tr,
+        Language::Vi,
+    ];
+
+    // Suffix of the game's `TextMap*.json` files.
+    pub fn text_map(self) -> &'static str {
+        match self {
+            Language::En => "EN",
+            Language::De => "DE",
+            Language::Es => "ES",
+            Language::Fr => "FR",
+            Language::Id => "ID",
+            Language::It => "IT",
+            Language::Pt => "PT",
+            Language::Ru => "RU",
+            Language::Tr => "TR",
+            Language::Vi => "VI",
+        }
+    }
+
+    // stardb has no Italian and Turkish translations.
+    pub fn stardb(self) -> &'static str {
+        match self {
+            Language::En | Language::It | Language::Tr => "en",
+            Language::De => "de",
+            Language::Es => "es-es",
+            Language::Fr => "fr",
+            Language::Id => "id",
+            Language::Pt => "pt-pt",
+            Language::Ru => "ru",
+            Language::Vi => "vi",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::En => "English",
+            Language::De => "Deutsch",
+            Language::Es => "Español",
+            Language::Fr => "Français",
+            Language::Id => "Bahasa Indonesia",
+            Language::It => "Italiano",
+            Language::Pt => "Português",
+            Language::Ru => "Русский",
+            Language::Tr => "Türkçe",
+            Language::Vi => "Tiếng Việt",
+        }
+    }
+}
@@ -0,0 +1,199 @@
+mod excel;
+mod language;
+pub mod prep;
+mod sets;
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::games::{Affix, SetKey, SlotKey, StatKey};
+
+pub use language::Language;
+
+// Bumped whenever the layout of `Tables` changes, so that stale files in the
+// storage dir are ignored instead of failing to deserialize.
+const FORMAT: u32 = 3;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[allow(non_snake_case)]
+pub struct ArtifactData {
+    pub setId: u32,
+    pub slotKey: SlotKey,
+    pub rarity: u32,
+}
+
+// Display names only, keys in exported files never depend on the language.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct Names {
+    pub sets: BTreeMap<u32, String>,
+    pub stats: BTreeMap<StatKey, String>,
+}
+
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum Source {
+    #[default]
+    Embedded,
+    Storage,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Tables {
+    pub format: u32,
+    pub version: String,
+    pub names: BTreeMap<Language, Names>,
+    pub artifacts: BTreeMap<u32, ArtifactData>,
+    pub main_props: BTreeMap<u32, StatKey>,
+    pub affixes: BTreeMap<u32, Affix>,
+    #[serde(skip)]
+    pub source: Source,
+    #[serde(skip)]
+    pub set_keys: BTreeMap<u32, SetKey>,
+}
+
+impl Tables {
+    // `storage` is the dir imported tables and set overrides are kept in.
+    pub fn load(storage: &Path) -> anyhow::Result<Self> {
+        let mut tables = match Self::from_storage(storage) {
+            Ok(Some(tables)) => tables,
+            Ok(None) => Self::embedded()?,
+            Err(e) => {
+                tracing::warn!("Ignoring stored data tables: {e}");
+                Self::embedded()?
+            }
+        };
+        tables.resolve_set_keys(storage);
+
+        Ok(tables)
+    }
+
+    // Generated by the `data-prep` subcommand, see `data/Readme.md`.
+    pub(crate) fn embedded() -> anyhow::Result<Self> {
+        let tables: Self = serde_json::from_str(include_str!("../../data/gi_tables.json"))?;
+        tables.check()?;
+        Ok(tables)
+    }
+
+    // Without these every captured artifact would be skipped as unknown.
+    fn check(&self) -> anyhow::Result<()> {
+        if self.artifacts.is_empty() || self.main_props.is_empty() || self.affixes.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Artifact data {} has no artifacts, main stats or substats, import the excel dumps",
+                self.version
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn from_storage(storage: &Path) -> anyhow::Result<Option<Self>> {
+        let path = path(storage);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let mut tables: Self = serde_json::from_slice(&std::fs::read(&path)?)?;
+        if tables.format != FORMAT {
+            return Err(anyhow::anyhow!(
+                "{} has format {}, expected {FORMAT}",
+                path.display(),
+                tables.format
+            ));
+        }
+        tables.source = Source::Storage;
+
+        tracing::info!(
+            "Loaded data tables {} from {}",
+            tables.version,
+            path.display()
+        );
+
+        Ok(Some(tables))
+    }
+
+    // Reads a folder of excel dumps and stores the result in the storage dir.
+    pub fn import(dir: &Path, storage: &Path) -> anyhow::Result<Self> {
+        let mut tables = excel::read_dir(dir)?;
+        tables.check()?;
+
+        let path = path(storage);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, prep::to_compact_json(&tables)?)?;
+        tables.source = Source::Storage;
+        tables.resolve_set_keys(storage);
+
+        tracing::info!(
+            "Imported data tables {} from {}",
+            tables.version,
+            dir.display()
+        );
+
+        Ok(tables)
+    }
+
+    // English names are only used to derive keys of sets missing from the set table.
+    fn resolve_set_keys(&mut self, storage: &Path) {
+        let english = self.names.get(&Language::En).map(|n| &n.sets);
+        self.set_keys = sets::resolve(english.unwrap_or(&BTreeMap::new()), storage);
+    }
+
+    pub fn set_name(&self, language: Language, set_key: SetKey) -> String {
+        let Some((set_id, _)) = self.set_keys.iter().find(|(_, key)| **key == set_key) else {
+            return set_key.to_string();
+        };
+
+        [language, Language::En]
+            .iter()
+            .find_map(|l| self.names.get(l)?.sets.get(set_id))
+            .cloned()
+            .unwrap_or_else(|| set_key.to_string())
+    }
+
+    pub fn stat_name(&self, language: Language, stat_key: StatKey) -> String {
+        [language, Language::En]
+            .iter()
+            .find_map(|l| self.names.get(l)?.stats.get(&stat_key))
+            .cloned()
+            .unwrap_or_else(|| stat_key.to_string())
+    }
+
+    pub fn describe(&self) -> String {
+        match self.source {
+            Source::Embedded => self.version.clone(),
+            Source::Storage => format!("{} (imported)", self.version),
+        }
+    }
+}
+
+fn dir(storage: &Path) -> PathBuf {
+    storage.join("data")
+}
+
+fn path(storage: &Path) -> PathBuf {
+    dir(storage).join("gi_tables.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_tables_are_filled_in() {
+        let tables: Tables =
+            serde_json::from_str(include_str!("../../data/gi_tables.json")).unwrap();
+
+        assert!(
+            !tables.artifacts.is_empty(),
+            "no artifacts, see data/Readme.md"
+        );
+        assert!(
+            !tables.main_props.is_empty(),
+            "no main props, see data/Readme.md"
+        );
+        assert!(!tables.affixes.is_empty(), "no affixes, see data/Readme.md");
+        assert!(tables.names.contains_key(&Language::En), "no English names");
+    }
+}
@@ -0,0 +1,81 @@
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf};
+
+use super::{Tables, excel};
+
+const USAGE: &str =
+    "Usage: stardb-exporter data-prep <dump dir> [output, default data/gi_tables.json]";
+
+pub fn run(args: &[String]) -> anyhow::Result<()> {
+    let (dir, out) = match args {
+        [dir] => (PathBuf::from(dir), PathBuf::from("data/gi_tables.json")),
+        [dir, out] => (PathBuf::from(dir), PathBuf::from(out)),
+        _ => return Err(anyhow::anyhow!(USAGE)),
+    };
+
+    let tables = excel::read_dir(&dir)?;
+    tables.check()?;
+    std::fs::write(&out, to_compact_json(&tables)?)?;
+
+    eprintln!(
+        "Wrote {} ({} languages, {} artifacts, {} main props, {} affixes) to {}",
+        tables.version,
+        tables.names.len(),
+        tables.artifacts.len(),
+        tables.main_props.len(),
+        tables.affixes.len(),
+        out.display()
+    );
+
+    Ok(())
+}
+
+// One entry per line, sorted by id, so that data updates are reviewable in diffs.
+pub fn to_compact_json(tables: &Tables) -> anyhow::Result<String> {
+    let mut out = String::new();
+
+    writeln!(out, "{{")?;
+    writeln!(out, "\"format\": {},", tables.format)?;
+    writeln!(
+        out,
+        "\"version\": {},",
+        serde_json::to_string(&tables.version)?
+    )?;
+    writeln!(out, "\"names\": {{")?;
+    for (i, (language, names)) in tables.names.iter().enumerate() {
+        writeln!(out, "{}: {{", serde_json::to_string(language)?)?;
+        write_section(&mut out, "sets", &names.sets, false)?;
+        write_section(&mut out, "stats", &names.stats, true)?;
+        writeln!(
+            out,
+            "}}{}",
+            if i + 1 == tables.names.len() { "" } else { "," }
+        )?;
+    }
+    writeln!(out, "}},")?;
+    write_section(&mut out, "artifacts", &tables.artifacts, false)?;
+    write_section(&mut out, "main_props", &tables.main_props, false)?;
+    write_section(&mut out, "affixes", &tables.affixes, true)?;
+    writeln!(out, "}}")?;
+
+    Ok(out)
+}
+
+fn write_section<K: std::fmt::Display, V: serde::Serialize>(
+    out: &mut String,
+    name: &str,
+    entries: &BTreeMap<K, V>,
+    last: bool,
+) -> anyhow::Result<()> {
+    writeln!(out, "\"{name}\": {{")?;
+    for (i, (id, value)) in entries.iter().enumerate() {
+        let separator = if i + 1 == entries.len() { "" } else { "," };
+        writeln!(
+            out,
+            "\"{id}\": {}{separator}",
+            serde_json::to_string(value)?
+        )?;
+    }
+    writeln!(out, "}}{}", if last { "" } else { "," })?;
+
+    Ok(())
+}
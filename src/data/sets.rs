@@ -0,0 +1,201 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::games::SetKey;
+
+// GOOD keys of every set id known at the time of this build. Sets released
+// later fall back to a key derived from their English name, which
+// `set_overrides.json` in the storage data dir can correct.
+const SET_KEYS: &[(u32, SetKey)] = &[
+    (10001, SetKey::ResolutionOfSojourner),
+    (10002, SetKey::BraveHeart),
+    (10003, SetKey::DefendersWill),
+    (10004, SetKey::TinyMiracle),
+    (10005, SetKey::Berserker),
+    (10006, SetKey::MartialArtist),
+    (10007, SetKey::Instructor),
+    (10008, SetKey::Gambler),
+    (10009, SetKey::TheExile),
+    (10010, SetKey::Adventurer),
+    (10011, SetKey::LuckyDog),
+    (10012, SetKey::Scholar),
+    (10013, SetKey::TravelingDoctor),
+    (14001, SetKey::PrayersForIllumination),
+    (14002, SetKey::PrayersForDestiny),
+    (14003, SetKey::PrayersForWisdom),
+    (14004, SetKey::PrayersToSpringtime),
+    (15001, SetKey::GladiatorsFinale),
+    (15002, SetKey::WanderersTroupe),
+    (15003, SetKey::NoblesseOblige),
+    (15004, SetKey::BloodstainedChivalry),
+    (15005, SetKey::MaidenBeloved),
+    (15006, SetKey::ViridescentVenerer),
+    (15007, SetKey::ArchaicPetra),
+    (15008, SetKey::RetracingBolide),
+    (15009, SetKey::Thundersoother),
+    (15010, SetKey::ThunderingFury),
+    (15011, SetKey::Lavawalker),
+    (15012, SetKey::CrimsonWitchOfFlames),
+    (15013, SetKey::BlizzardStrayer),
+    (15014, SetKey::HeartOfDepth),
+    (15015, SetKey::TenacityOfTheMillelith),
+    (15016, SetKey::PaleFlame),
+    (15017, SetKey::ShimenawasReminiscence),
+    (15018, SetKey::EmblemOfSeveredFate),
+    (15019, SetKey::HuskOfOpulentDreams),
+    (15020, SetKey::OceanHuedClam),
+    (15021, SetKey::VermillionHereafter),
+    (15022, SetKey::EchoesOfAnOffering),
+    (15023, SetKey::DeepwoodMemories),
+    (15024, SetKey::GildedDreams),
+    (15025, SetKey::DesertPavilionChronicle),
+    (15026, SetKey::FlowerOfParadiseLost),
+    (15027, SetKey::NymphsDream),
+    (15028, SetKey::VourukashasGlow),
+    (15029, SetKey::MarechausseeHunter),
+    (15030, SetKey::GoldenTroupe),
+    (15031, SetKey::SongOfDaysPast),
+    (15032, SetKey::NighttimeWhispersInTheEchoingWoods),
+    (15033, SetKey::FragmentOfHarmonicWhimsy),
+    (15034, SetKey::UnfinishedReverie),
+    (15035, SetKey::ScrollOfTheHeroOfCinderCity),
+    (15036, SetKey::ObsidianCodex),
+    (15037, SetKey::LongNightsOath),
+    (15038, SetKey::FinaleOfTheDeepGalleries),
+    (15039, SetKey::NightOfTheSkysUnveiling),
+    (15040, SetKey::SilkenMoonsSerenade),
+    (15041, SetKey::AubadeOfMorningstarAndMoon),
+    (15042, SetKey::ADayCarvedFromRisingWinds),
+];
+
+fn map_set_name_to_good(input: &str) -> String {
+    let mut result = String::new();
+    let mut capitalize_next = true;
+
+    for c in input.chars() {
+        if c.is_alphabetic() {
+            if capitalize_next {
+                result.extend(c.to_uppercase());
+                capitalize_next = false;
+            } else {
+                result.extend(c.to_lowercase());
+            }
+        } else if c != '\'' {
+            capitalize_next = true;
+        }
+    }
+
+    result
+}
+
+pub fn overrides_path(storage: &Path) -> PathBuf {
+    super::dir(storage).join("set_overrides.json")
+}
+
+// Set id -> GOOD key overrides, e.g. `{ "15043": "SomeNewSet" }`.
+fn load_overrides(storage: &Path) -> BTreeMap<u32, SetKey> {
+    let path = overrides_path(storage);
+    if !path.exists() {
+        return BTreeMap::new();
+    }
+
+    match std::fs::read(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| Ok(serde_json::from_slice(&bytes)?))
+    {
+        Ok(overrides) => overrides,
+        Err(e) => {
+            tracing::warn!("Ignoring {}: {e}", path.display());
+            BTreeMap::new()
+        }
+    }
+}
+
+pub fn resolve(names: &BTreeMap<u32, String>, storage: &Path) -> BTreeMap<u32, SetKey> {
+    resolve_with(names, load_overrides(storage))
+}
+
+fn resolve_with(
+    names: &BTreeMap<u32, String>,
+    overrides: BTreeMap<u32, SetKey>,
+) -> BTreeMap<u32, SetKey> {
+    let mut keys: BTreeMap<u32, SetKey> = SET_KEYS.iter().copied().collect();
+
+    for (set_id, name) in names {
+        if keys.contains_key(set_id) || overrides.contains_key(set_id) {
+            continue;
+        }
+
+        match SetKey::from_good(&map_set_name_to_good(name)) {
+            Some(key) => {
+                if key.is_known() {
+                    tracing::info!("Set {set_id} is not in the set table, derived {key}");
+                } else {
+                    tracing::warn!(
+                        "Set {set_id} is not in the set table, derived new set {key} from \"{name}\""
+                    );
+                }
+                keys.insert(*set_id, key);
+            }
+            None => tracing::warn!("Set {set_id}: couldn't derive a GOOD key from \"{name}\""),
+        }
+    }
+
+    keys.extend(overrides);
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    #[test]
+    fn every_known_set_id_maps_to_a_valid_good_key() {
+        let keys = resolve_with(&BTreeMap::new(), BTreeMap::new());
+        assert_eq!(keys.len(), SET_KEYS.len(), "duplicate set id");
+
+        let mut seen = BTreeSet::new();
+        for (set_id, key) in keys {
+            assert!(key.is_known(), "{set_id}: {key}");
+            assert_eq!(SetKey::from_good(key.as_str()), Some(key), "{set_id}");
+            assert!(seen.insert(key), "{set_id}: {key} used twice");
+        }
+    }
+
+    #[test]
+    fn name_derivation_is_only_a_fallback() {
+        let names = BTreeMap::from([
+            (15001, "Some Translated Name".to_string()),
+            (99001, "Shepherd's Song of the Stars".to_string()),
+            (99002, "???".to_string()),
+        ]);
+
+        let keys = resolve_with(&names, BTreeMap::new());
+        assert_eq!(keys[&15001], SetKey::GladiatorsFinale);
+        assert_eq!(keys[&99001].as_str(), "ShepherdsSongOfTheStars");
+        assert!(!keys.contains_key(&99002));
+    }
+
+    #[test]
+    fn overrides_take_precedence() {
+        let names = BTreeMap::from([(99001, "Badly Named Set".to_string())]);
+        let overrides: BTreeMap<u32, SetKey> =
+            serde_json::from_str(r#"{ "15001": "WanderersTroupe", "99001": "ProperlyNamedSet" }"#)
+                .unwrap();
+
+        let keys = resolve_with(&names, overrides);
+        assert_eq!(keys[&15001], SetKey::WanderersTroupe);
+        assert_eq!(keys[&99001].as_str(), "ProperlyNamedSet");
+    }
+
+    #[test]
+    fn overrides_reject_invalid_keys() {
+        assert!(
+            serde_json::from_str::<BTreeMap<u32, SetKey>>(r#"{ "99001": "not a key" }"#).is_err()
+        );
+    }
+}
@@ -0,0 +1,120 @@
+// Machine readable events for scripts, one JSON object per line (NDJSON).
+
+use std::{fs::OpenOptions, io::Write, sync::Mutex};
+
+use crate::{games::Progress, snapshots};
+
+#[derive(serde::Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    DeviceReady {
+        device: usize,
+    },
+    DeviceRestarting {
+        device: usize,
+    },
+    Handshake,
+    CommandMatched {
+        command: &'static str,
+    },
+    Waiting {
+        status: String,
+    },
+    // `data` is what would be exported: GOOD, stardb achievements or the pull url.
+    Result {
+        game: &'static str,
+        kind: &'static str,
+        data: serde_json::Value,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl From<Progress> for Event {
+    fn from(progress: Progress) -> Self {
+        match progress {
+            Progress::DeviceReady(device) => Event::DeviceReady { device },
+            Progress::DeviceRestarting(device) => Event::DeviceRestarting { device },
+            Progress::Handshake => Event::Handshake,
+            Progress::CommandMatched(command) => Event::CommandMatched { command },
+        }
+    }
+}
+
+pub struct EventLog {
+    out: Mutex<Box<dyn Write + Send>>,
+    stdout: bool,
+}
+
+impl EventLog {
+    // `-` is stdout, anything else a file that is appended to.
+    pub fn open(target: &str) -> anyhow::Result<Self> {
+        let (out, stdout): (Box<dyn Write + Send>, _) = match target {
+            "-" => (Box::new(std::io::stdout()), true),
+            path => (
+                Box::new(OpenOptions::new().create(true).append(true).open(path)?),
+                false,
+            ),
+        };
+
+        Ok(Self {
+            out: Mutex::new(out),
+            stdout,
+        })
+    }
+
+    pub fn is_stdout(&self) -> bool {
+        self.stdout
+    }
+
+    pub fn emit(&self, event: Event) {
+        let mut out = self.out.lock().unwrap();
+        if let Err(e) = out
+            .write_all(line(&event, snapshots::now()).as_bytes())
+            .and_then(|()| out.flush())
+        {
+            tracing::warn!("Couldn't write event: {e}");
+        }
+    }
+}
+
+fn line(event: &Event, timestamp: u64) -> String {
+    #[derive(serde::Serialize)]
+    struct Line<'a> {
+        timestamp: u64,
+        #[serde(flatten)]
+        event: &'a Event,
+    }
+
+    let mut line = serde_json::to_string(&Line { timestamp, event }).unwrap_or_default();
+    line.push('\n');
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_one_tagged_object_per_line() {
+        assert_eq!(
+            line(&Event::from(Progress::DeviceReady(1)), 10),
+            "{\"timestamp\":10,\"event\":\"device_ready\",\"device\":1}\n"
+        );
+        assert_eq!(
+            line(&Event::Handshake, 10),
+            "{\"timestamp\":10,\"event\":\"handshake\"}\n"
+        );
+
+        let result = Event::Result {
+            game: "hsr",
+            kind: "achievements",
+            data: serde_json::json!({ "hsr_achievements": [1, 2] }),
+        };
+        assert_eq!(
+            line(&result, 10),
+            "{\"timestamp\":10,\"event\":\"result\",\"game\":\"hsr\",\"kind\":\"achievements\",\"data\":{\"hsr_achievements\":[1,2]}}\n"
+        );
+    }
+}
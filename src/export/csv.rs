@@ -0,0 +1,55 @@
+use super::{Cell, Sheet};
+
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn cell(cell: &Cell) -> String {
+    match cell {
+        Cell::Text(text) => field(text),
+        Cell::Number(number) => number.to_string(),
+        Cell::Bool(bool) => bool.to_string(),
+        Cell::Empty => String::new(),
+    }
+}
+
+pub fn write(sheet: &Sheet) -> String {
+    let mut out = sheet.header.join(",");
+    out.push('\n');
+
+    for row in &sheet.rows {
+        let row: Vec<_> = row.iter().map(cell).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_fields_that_need_it() {
+        let sheet = Sheet {
+            name: "Test",
+            header: vec!["name", "value", "done", "empty"],
+            rows: vec![vec![
+                Cell::Text("Wanderer's \"Troupe\", 2pc".to_string()),
+                Cell::Number(46.6),
+                Cell::Bool(true),
+                Cell::Empty,
+            ]],
+        };
+
+        assert_eq!(
+            write(&sheet),
+            "name,value,done,empty\n\"Wanderer's \"\"Troupe\"\", 2pc\",46.6,true,\n"
+        );
+    }
+}
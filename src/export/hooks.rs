@@ -0,0 +1,213 @@
+// User commands that run after an export, like `upload.sh {file} {game} {kind} {uid}`.
+// Placeholders are filled in per argument and never passed through a shell, so
+// paths with spaces stay one argument. The same values are in the environment
+// as STARDB_FILE, STARDB_GAME, STARDB_KIND and STARDB_UID.
+
+use std::{
+    io::Read,
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::games::Game;
+
+pub struct Export<'a> {
+    pub file: &'a Path,
+    pub game: Game,
+    pub kind: &'a str,
+    pub uid: Option<&'a str>,
+}
+
+pub struct Outcome {
+    pub success: bool,
+    // Exit code, or how the command was stopped.
+    pub status: String,
+    // Stdout followed by stderr, trimmed.
+    pub output: String,
+}
+
+// One command per non-empty line, `#` starts a comment line.
+pub fn commands(hooks: &str) -> impl Iterator<Item = &str> {
+    hooks
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+}
+
+// Hooks still running after this are killed.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+pub fn run(command: &str, export: &Export) -> anyhow::Result<Outcome> {
+    run_with_timeout(command, export, TIMEOUT)
+}
+
+fn run_with_timeout(command: &str, export: &Export, timeout: Duration) -> anyhow::Result<Outcome> {
+    let file = export.file.to_string_lossy();
+    let uid = export.uid.unwrap_or_default();
+
+    let args: Vec<String> = split(command)?
+        .into_iter()
+        .map(|arg| {
+            arg.replace("{file}", &file)
+                .replace("{game}", export.game.id())
+                .replace("{kind}", export.kind)
+                .replace("{uid}", uid)
+        })
+        .collect();
+    let Some((program, args)) = args.split_first() else {
+        return Err(anyhow::anyhow!("Empty command"));
+    };
+
+    let mut child = Command::new(program)
+        .args(args)
+        .env("STARDB_FILE", export.file)
+        .env("STARDB_GAME", export.game.id())
+        .env("STARDB_KIND", export.kind)
+        .env("STARDB_UID", uid)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Read while waiting, so that a full pipe can't block the hook.
+    let stdout = child.stdout.take().map(read_to_end);
+    let stderr = child.stderr.take().map(read_to_end);
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() > deadline {
+            child.kill()?;
+            child.wait()?;
+            // Whatever it started may still hold the pipes open, so the
+            // output is left behind.
+            return Ok(Outcome {
+                success: false,
+                status: format!("killed after {} seconds", timeout.as_secs_f32()),
+                output: String::new(),
+            });
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+
+    let output = |reader: Option<thread::JoinHandle<Vec<u8>>>| {
+        let bytes = reader.and_then(|r| r.join().ok()).unwrap_or_default();
+        String::from_utf8_lossy(&bytes).into_owned()
+    };
+
+    let success = status.success();
+    let status = match status.code() {
+        Some(code) => format!("exit code {code}"),
+        None => status.to_string(),
+    };
+    let output = format!("{}\n{}", output(stdout), output(stderr));
+
+    Ok(Outcome {
+        success,
+        status,
+        output: output.trim().to_string(),
+    })
+}
+
+fn read_to_end(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = pipe.read_to_end(&mut bytes);
+        bytes
+    })
+}
+
+// Splits on whitespace outside of single or double quotes.
+fn split(command: &str) -> anyhow::Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut arg = String::new();
+    let mut in_arg = false;
+    let mut quote = None;
+
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => arg.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut arg));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                arg.push(c);
+                in_arg = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err(anyhow::anyhow!("Unclosed quote in {command}"));
+    }
+    if in_arg {
+        args.push(arg);
+    }
+
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_quoted_arguments() {
+        assert_eq!(
+            split(r#"upload.sh {file} "my game" '' --kind={kind}"#).unwrap(),
+            ["upload.sh", "{file}", "my game", "", "--kind={kind}"]
+        );
+        assert!(split("upload.sh 'unclosed").is_err());
+    }
+
+    #[test]
+    fn skips_blank_and_comment_lines() {
+        let hooks = "upload.sh {file}\n\n  # disabled.sh\nnotify.sh {kind}\n";
+        assert_eq!(
+            commands(hooks).collect::<Vec<_>>(),
+            ["upload.sh {file}", "notify.sh {kind}"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn passes_placeholders_and_environment() {
+        let export = Export {
+            file: Path::new("/tmp/gi artifacts.json"),
+            game: Game::Gi,
+            kind: "artifacts",
+            uid: Some("800000000"),
+        };
+
+        let outcome = run(
+            r#"sh -c 'echo "$1|$2|$3|$4|$STARDB_FILE"' hook {file} {game} {kind} {uid}"#,
+            &export,
+        )
+        .unwrap();
+        assert!(outcome.success);
+        assert_eq!(
+            outcome.output,
+            "/tmp/gi artifacts.json|gi|artifacts|800000000|/tmp/gi artifacts.json"
+        );
+
+        let outcome = run("sh -c 'exit 3'", &export).unwrap();
+        assert!(!outcome.success);
+        assert_eq!(outcome.status, "exit code 3");
+
+        let outcome = run_with_timeout("sleep 10", &export, Duration::from_millis(200)).unwrap();
+        assert!(!outcome.success);
+        assert_eq!(outcome.status, "killed after 0.2 seconds");
+    }
+}
@@ -0,0 +1,326 @@
+mod csv;
+pub mod hooks;
+mod xlsx;
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    data::{Language, Tables},
+    games::{
+        Artifact, Game, Good,
+        analytics::{self, Metrics, Weights},
+        validate,
+    },
+};
+
+// UTC `YYYY-MM-DD HH:MM:SS`.
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+
+    // Civil date from days since the epoch, see
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+// `gi_artifacts_800000000_2026-01-31_18-00-00.json`, sorting by game, data
+// type, UID and then time.
+pub fn file_name(
+    game: Game,
+    kind: &str,
+    uid: Option<&str>,
+    timestamp: u64,
+    extension: &str,
+) -> String {
+    let time = format_timestamp(timestamp)
+        .replace(' ', "_")
+        .replace(':', "-");
+
+    format!(
+        "{}_{kind}_{}_{time}.{extension}",
+        game.id(),
+        uid.unwrap_or("unknown")
+    )
+}
+
+pub fn auto_save(dir: &Path, file_name: &str, bytes: &[u8]) -> anyhow::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(file_name);
+    std::fs::write(&path, bytes)?;
+    Ok(path)
+}
+
+// The payload of the stardb achievement import.
+pub fn stardb_achievements(game: Game, achievements: &[u32]) -> anyhow::Result<Vec<u8>> {
+    let key = match game {
+        Game::Hsr => "hsr_achievements",
+        Game::Gi => "gi_achievements",
+        Game::Zzz => return Err(anyhow::anyhow!("No achievements for this game")),
+    };
+
+    Ok(serde_json::to_vec(
+        &serde_json::json!({ key: achievements }),
+    )?)
+}
+
+// Every GOOD export goes through here, so that none of them silently carries
+// artifacts an optimizer would reject.
+pub fn good(artifacts: &[Artifact], allow_invalid: bool) -> anyhow::Result<Vec<u8>> {
+    check(artifacts, allow_invalid)?;
+    Ok(serde_json::to_vec(&Good::new(artifacts))?)
+}
+
+// The number of artifacts that failed validation, an error if there are any
+// and they aren't allowed.
+pub fn check(artifacts: &[Artifact], allow_invalid: bool) -> anyhow::Result<usize> {
+    let invalid = validate::validate_all(artifacts).len();
+    if invalid > 0 {
+        if !allow_invalid {
+            return Err(anyhow::anyhow!("{invalid} artifacts failed validation"));
+        }
+        tracing::warn!("Exporting {invalid} artifacts that failed validation");
+    }
+
+    Ok(invalid)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Csv,
+    Xlsx,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Xlsx => "xlsx",
+        }
+    }
+}
+
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Empty,
+}
+
+// A single table, written as a CSV file or a one-sheet workbook.
+pub struct Sheet {
+    pub name: &'static str,
+    pub header: Vec<&'static str>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Sheet {
+    pub fn to_bytes(&self, format: Format) -> anyhow::Result<Vec<u8>> {
+        match format {
+            Format::Csv => Ok(csv::write(self).into_bytes()),
+            Format::Xlsx => xlsx::write(self),
+        }
+    }
+}
+
+// One row per artifact. Keys are the GOOD ones, names follow the language.
+pub fn artifacts(artifacts: &[Artifact], tables: Option<&Tables>, language: Language) -> Sheet {
+    let header = vec![
+        "set",
+        "set_name",
+        "slot",
+        "main_stat",
+        "level",
+        "rarity",
+        "lock",
+        "substat_1",
+        "value_1",
+        "substat_2",
+        "value_2",
+        "substat_3",
+        "value_3",
+        "substat_4",
+        "value_4",
+    ];
+
+    let rows = artifacts
+        .iter()
+        .map(|artifact| {
+            let mut row = vec![
+                Cell::Text(artifact.setKey.to_string()),
+                Cell::Text(
+                    tables
+                        .map(|t| t.set_name(language, artifact.setKey))
+                        .unwrap_or_else(|| artifact.setKey.to_string()),
+                ),
+                Cell::Text(artifact.slotKey.to_string()),
+                Cell::Text(artifact.mainStatKey.to_string()),
+                Cell::Number(artifact.level.into()),
+                Cell::Number(artifact.rarity.into()),
+                Cell::Bool(artifact.lock),
+            ];
+            for i in 0..4 {
+                match artifact.substats.get(i) {
+                    Some(substat) => {
+                        row.push(Cell::Text(substat.key.to_string()));
+                        row.push(Cell::Number(substat.value));
+                    }
+                    None => {
+                        row.push(Cell::Empty);
+                        row.push(Cell::Empty);
+                    }
+                }
+            }
+            row
+        })
+        .collect();
+
+    Sheet {
+        name: "Artifacts",
+        header,
+        rows,
+    }
+}
+
+// The analytics view, one row per artifact in capture order.
+pub fn analytics(artifacts: &[Artifact], weights: &Weights, threshold: f64) -> Sheet {
+    let header = vec![
+        "index",
+        "set",
+        "slot",
+        "main_stat",
+        "level",
+        "rarity",
+        "lock",
+        "crit_value",
+        "roll_value",
+        "rolls",
+        "efficiency",
+        "potential",
+        "fodder",
+    ];
+
+    let rows = artifacts
+        .iter()
+        .enumerate()
+        .map(|(i, artifact)| {
+            let metrics = Metrics::new(artifact, weights);
+            vec![
+                Cell::Number(i as f64),
+                Cell::Text(artifact.setKey.to_string()),
+                Cell::Text(artifact.slotKey.to_string()),
+                Cell::Text(artifact.mainStatKey.to_string()),
+                Cell::Number(artifact.level.into()),
+                Cell::Number(artifact.rarity.into()),
+                Cell::Bool(artifact.lock),
+                rounded(metrics.crit_value, 1),
+                rounded(metrics.roll_value, 2),
+                Cell::Number(metrics.rolls as f64),
+                rounded(metrics.efficiency, 3),
+                rounded(metrics.potential, 2),
+                Cell::Bool(analytics::is_fodder(artifact, &metrics, threshold)),
+            ]
+        })
+        .collect();
+
+    Sheet {
+        name: "Analytics",
+        header,
+        rows,
+    }
+}
+
+fn rounded(value: f64, decimals: i32) -> Cell {
+    let scale = 10f64.powi(decimals);
+    Cell::Number((value * scale).round() / scale)
+}
+
+// Every known achievement, with whether it was found completed.
+pub fn achievements(completed: &[u32], names: &BTreeMap<u32, String>) -> Sheet {
+    let mut ids: Vec<u32> = names.keys().copied().collect();
+    ids.extend(completed.iter().filter(|id| !names.contains_key(id)));
+    ids.sort();
+
+    let rows = ids
+        .into_iter()
+        .map(|id| {
+            vec![
+                Cell::Number(id.into()),
+                names
+                    .get(&id)
+                    .map_or(Cell::Empty, |n| Cell::Text(n.clone())),
+                Cell::Bool(completed.contains(&id)),
+            ]
+        })
+        .collect();
+
+    Sheet {
+        name: "Achievements",
+        header: vec!["id", "name", "completed"],
+        rows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_timestamps_as_utc() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(format_timestamp(1_760_877_296), "2025-10-19 12:34:56");
+    }
+
+    #[test]
+    fn file_names_are_predictable() {
+        assert_eq!(
+            file_name(
+                Game::Gi,
+                "artifacts",
+                Some("800000000"),
+                1_760_877_296,
+                "json"
+            ),
+            "gi_artifacts_800000000_2025-10-19_12-34-56.json"
+        );
+        assert_eq!(
+            file_name(Game::Hsr, "achievements", None, 0, "xlsx"),
+            "hsr_achievements_unknown_1970-01-01_00-00-00.xlsx"
+        );
+    }
+    #[test]
+    fn invalid_artifacts_are_only_exported_when_allowed() {
+        use crate::games::{SetKey, SlotKey, StatKey};
+
+        let artifact = Artifact {
+            setKey: SetKey::GladiatorsFinale,
+            slotKey: SlotKey::Flower,
+            level: 0,
+            rarity: 5,
+            mainStatKey: StatKey::Hp,
+            lock: false,
+            substats: Vec::new(),
+        };
+
+        assert!(good(std::slice::from_ref(&artifact), false).is_err());
+        assert_eq!(check(&[artifact], true).unwrap(), 1);
+    }
+}
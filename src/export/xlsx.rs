@@ -0,0 +1,163 @@
+use std::io::{Cursor, Write};
+
+use super::{Cell, Sheet};
+
+// The smallest package spreadsheet apps accept: one sheet, inline strings
+// and no styles.
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/></Types>"#;
+
+const RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#;
+
+const WORKBOOK_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#;
+
+// Control characters other than tabs and line breaks can't appear in XML at
+// all, not even escaped, so they are dropped.
+fn escape(text: &str) -> String {
+    text.chars()
+        .filter(|c| {
+            matches!(c, '\t' | '\n' | '\r')
+                || !(c.is_control() || matches!(c, '\u{fffe}' | '\u{ffff}'))
+        })
+        .collect::<String>()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// `A`, `B`, ..., `Z`, `AA`, ...
+fn column(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap()
+}
+
+fn cell(reference: &str, cell: &Cell) -> String {
+    match cell {
+        Cell::Text(text) => format!(
+            r#"<c r="{reference}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+            escape(text)
+        ),
+        // Excel has no NaN or infinity and refuses the whole file over one.
+        Cell::Number(number) if !number.is_finite() => String::new(),
+        Cell::Number(number) => format!(r#"<c r="{reference}"><v>{number}</v></c>"#),
+        Cell::Bool(bool) => format!(r#"<c r="{reference}" t="b"><v>{}</v></c>"#, u8::from(*bool)),
+        Cell::Empty => String::new(),
+    }
+}
+
+fn worksheet(sheet: &Sheet) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#,
+    );
+
+    let header = sheet
+        .header
+        .iter()
+        .map(|h| Cell::Text(h.to_string()))
+        .collect();
+    for (r, row) in std::iter::once(&header).chain(&sheet.rows).enumerate() {
+        xml.push_str(&format!(r#"<row r="{}">"#, r + 1));
+        for (c, value) in row.iter().enumerate() {
+            xml.push_str(&cell(&format!("{}{}", column(c), r + 1), value));
+        }
+        xml.push_str("</row>");
+    }
+
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+pub fn write(sheet: &Sheet) -> anyhow::Result<Vec<u8>> {
+    let workbook = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="{}" sheetId="1" r:id="rId1"/></sheets></workbook>"#,
+        escape(sheet.name)
+    );
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+
+    for (name, content) in [
+        ("[Content_Types].xml", CONTENT_TYPES),
+        ("_rels/.rels", RELS),
+        ("xl/workbook.xml", &workbook),
+        ("xl/_rels/workbook.xml.rels", WORKBOOK_RELS),
+        ("xl/worksheets/sheet1.xml", &worksheet(sheet)),
+    ] {
+        zip.start_file(name, options)?;
+        zip.write_all(content.as_bytes())?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    #[test]
+    fn names_columns_like_spreadsheets() {
+        assert_eq!(column(0), "A");
+        assert_eq!(column(25), "Z");
+        assert_eq!(column(26), "AA");
+        assert_eq!(column(27), "AB");
+        assert_eq!(column(701), "ZZ");
+        assert_eq!(column(702), "AAA");
+    }
+
+    #[test]
+    fn drops_characters_xml_cannot_hold() {
+        assert_eq!(escape("Set\u{0}\u{1b}[0m\tname\u{ffff}"), "Set[0m\tname");
+    }
+
+    #[test]
+    fn leaves_non_finite_numbers_empty() {
+        assert_eq!(cell("A1", &Cell::Number(f64::NAN)), "");
+        assert_eq!(cell("A1", &Cell::Number(f64::INFINITY)), "");
+        assert_eq!(
+            cell("A1", &Cell::Number(1.5)),
+            r#"<c r="A1"><v>1.5</v></c>"#
+        );
+    }
+
+    #[test]
+    fn writes_a_readable_package() {
+        let sheet = Sheet {
+            name: "Artifacts",
+            header: vec!["set", "level", "lock"],
+            rows: vec![vec![
+                Cell::Text("<Gladiator & Co>".to_string()),
+                Cell::Number(20.0),
+                Cell::Bool(false),
+            ]],
+        };
+
+        let bytes = write(&sheet).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(archive.len(), 5);
+
+        let mut xml = String::new();
+        archive
+            .by_name("xl/worksheets/sheet1.xml")
+            .unwrap()
+            .read_to_string(&mut xml)
+            .unwrap();
+        assert!(xml.contains(r#"<c r="A2" t="inlineStr"><is><t xml:space="preserve">&lt;Gladiator &amp; Co&gt;</t></is></c>"#));
+        assert!(xml.contains(r#"<c r="B2"><v>20</v></c>"#));
+        assert!(xml.contains(r#"<c r="C2" t="b"><v>0</v></c>"#));
+    }
+}
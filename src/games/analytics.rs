@@ -0,0 +1,306 @@
+use std::collections::BTreeMap;
+
+use super::{Artifact, SetKey, SlotKey, StatKey, max_level};
+
+// Highest substat roll by rarity, in GOOD units.
+fn max_roll(key: StatKey, rarity: u32) -> Option<f64> {
+    let (four, five) = match key {
+        StatKey::Hp => (239.0, 298.75),
+        StatKey::HpPercent | StatKey::AtkPercent => (4.66, 5.83),
+        StatKey::Atk => (15.56, 19.45),
+        StatKey::Def => (18.52, 23.15),
+        StatKey::DefPercent => (5.83, 7.29),
+        StatKey::CritRate => (3.11, 3.89),
+        StatKey::CritDmg => (6.22, 7.77),
+        StatKey::EnerRech => (5.18, 6.48),
+        StatKey::EleMas => (18.65, 23.31),
+        _ => return None,
+    };
+
+    match rarity {
+        4 => Some(four),
+        5 => Some(five),
+        _ => None,
+    }
+}
+
+// Tiers are 70%, 80%, 90% and 100% of the highest roll.
+const AVERAGE_TIER: f64 = 0.85;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Weights(pub BTreeMap<StatKey, f64>);
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self(BTreeMap::from([
+            (StatKey::CritRate, 1.0),
+            (StatKey::CritDmg, 1.0),
+            (StatKey::AtkPercent, 0.5),
+            (StatKey::EnerRech, 0.5),
+            (StatKey::EleMas, 0.5),
+        ]))
+    }
+}
+
+impl Weights {
+    // Every stat that can roll as a substat.
+    pub const SUBSTATS: [StatKey; 10] = [
+        StatKey::CritRate,
+        StatKey::CritDmg,
+        StatKey::AtkPercent,
+        StatKey::HpPercent,
+        StatKey::DefPercent,
+        StatKey::EnerRech,
+        StatKey::EleMas,
+        StatKey::Atk,
+        StatKey::Hp,
+        StatKey::Def,
+    ];
+
+    pub fn get(&self, key: StatKey) -> f64 {
+        self.0.get(&key).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Metrics {
+    pub crit_value: f64,
+    // Weighted substat total in units of highest rolls.
+    pub roll_value: f64,
+    pub rolls: u32,
+    // Share of the rolls so far that went into weighted stats at their highest tier.
+    pub efficiency: f64,
+    // Expected roll value once upgraded to max level.
+    pub potential: f64,
+    pub max_rolls: u32,
+}
+
+impl Metrics {
+    pub fn new(artifact: &Artifact, weights: &Weights) -> Self {
+        let crit_value = artifact
+            .substats
+            .iter()
+            .map(|s| match s.key {
+                StatKey::CritRate => 2.0 * s.value,
+                StatKey::CritDmg => s.value,
+                _ => 0.0,
+            })
+            .sum();
+
+        let mut roll_value = 0.0;
+        let mut unweighted = 0.0;
+        for substat in &artifact.substats {
+            if let Some(max) = max_roll(substat.key, artifact.rarity) {
+                roll_value += weights.get(substat.key) * substat.value / max;
+                unweighted += substat.value / max;
+            }
+        }
+
+        let upgrades = artifact.level / 4;
+        let max_upgrades = max_level(artifact.rarity).unwrap_or_default() / 4;
+
+        // With four lines, whether the artifact dropped with three or four
+        // decides the roll count. Pick the count closest to the rolled total.
+        let rolls = match artifact.substats.len() {
+            4 => {
+                let estimate = unweighted / AVERAGE_TIER;
+                let (three, four) = (3 + upgrades, 4 + upgrades);
+                if (estimate - three as f64).abs() < (estimate - four as f64).abs() {
+                    three
+                } else {
+                    four
+                }
+            }
+            lines => lines as u32 + upgrades,
+        };
+        let max_rolls = rolls + max_upgrades.saturating_sub(upgrades);
+
+        // Each upgrade rolls one of the four lines at random. A line added
+        // later is unknown, so it counts as the average of the known ones.
+        let line_weight = if artifact.substats.is_empty() {
+            0.0
+        } else {
+            artifact
+                .substats
+                .iter()
+                .map(|s| weights.get(s.key))
+                .sum::<f64>()
+                / artifact.substats.len() as f64
+        };
+        let potential = roll_value
+            + f64::from(max_upgrades.saturating_sub(upgrades)) * AVERAGE_TIER * line_weight;
+
+        Self {
+            crit_value,
+            roll_value,
+            rolls,
+            efficiency: if rolls == 0 {
+                0.0
+            } else {
+                roll_value / rolls as f64
+            },
+            potential,
+            max_rolls,
+        }
+    }
+
+    // Expected share of weighted rolls at max level.
+    pub fn potential_efficiency(&self) -> f64 {
+        if self.max_rolls == 0 {
+            0.0
+        } else {
+            self.potential / self.max_rolls as f64
+        }
+    }
+}
+
+// Unlocked artifacts that can't be worth much even when fully upgraded.
+pub fn is_fodder(artifact: &Artifact, metrics: &Metrics, threshold: f64) -> bool {
+    !artifact.lock && (artifact.rarity < 5 || metrics.potential_efficiency() < threshold)
+}
+
+#[derive(Default)]
+pub struct Counts {
+    pub sets: BTreeMap<SetKey, usize>,
+    pub slots: BTreeMap<SlotKey, usize>,
+    pub main_stats: BTreeMap<StatKey, usize>,
+}
+
+impl Counts {
+    pub fn new(artifacts: &[Artifact]) -> Self {
+        let mut counts = Self::default();
+        for artifact in artifacts {
+            *counts.sets.entry(artifact.setKey).or_default() += 1;
+            *counts.slots.entry(artifact.slotKey).or_default() += 1;
+            *counts.main_stats.entry(artifact.mainStatKey).or_default() += 1;
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::Substat;
+
+    fn artifact(level: u32, lock: bool, substats: &[(StatKey, f64)]) -> Artifact {
+        Artifact {
+            setKey: SetKey::GladiatorsFinale,
+            slotKey: SlotKey::Flower,
+            level,
+            rarity: 5,
+            mainStatKey: StatKey::Hp,
+            lock,
+            substats: substats
+                .iter()
+                .map(|(key, value)| Substat {
+                    key: *key,
+                    value: *value,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn crit_value_counts_crit_rate_twice() {
+        let artifact = artifact(
+            20,
+            true,
+            &[
+                (StatKey::CritRate, 10.5),
+                (StatKey::CritDmg, 21.0),
+                (StatKey::Atk, 19.0),
+            ],
+        );
+        let metrics = Metrics::new(&artifact, &Weights::default());
+        assert_eq!(metrics.crit_value, 42.0);
+    }
+
+    #[test]
+    fn perfect_rolls_into_weighted_stats_are_fully_efficient() {
+        // Four max initial rolls and five max upgrades into crit rate.
+        let artifact = artifact(
+            20,
+            true,
+            &[
+                (StatKey::CritRate, 3.89 * 6.0),
+                (StatKey::CritDmg, 7.77),
+                (StatKey::AtkPercent, 5.83),
+                (StatKey::EnerRech, 6.48),
+            ],
+        );
+        let weights = Weights(Weights::SUBSTATS.iter().map(|k| (*k, 1.0)).collect());
+
+        let metrics = Metrics::new(&artifact, &weights);
+        assert_eq!(metrics.rolls, 9);
+        assert!((metrics.efficiency - 1.0).abs() < 1e-9, "{metrics:?}");
+        assert!((metrics.potential - metrics.roll_value).abs() < 1e-9);
+    }
+
+    #[test]
+    fn roll_count_tells_three_and_four_line_drops_apart() {
+        let three = artifact(
+            4,
+            false,
+            &[
+                (StatKey::Hp, 209.13),
+                (StatKey::Def, 16.2),
+                (StatKey::Atk, 13.62),
+                (StatKey::EleMas, 16.32),
+            ],
+        );
+        assert_eq!(Metrics::new(&three, &Weights::default()).rolls, 4);
+
+        let four = artifact(
+            4,
+            false,
+            &[
+                (StatKey::Hp, 597.5),
+                (StatKey::Def, 16.2),
+                (StatKey::Atk, 13.62),
+                (StatKey::EleMas, 16.32),
+            ],
+        );
+        assert_eq!(Metrics::new(&four, &Weights::default()).rolls, 5);
+    }
+
+    #[test]
+    fn flat_stat_artifacts_are_fodder_unless_locked() {
+        let substats = [
+            (StatKey::Hp, 209.13),
+            (StatKey::Def, 16.2),
+            (StatKey::Atk, 13.62),
+        ];
+        let weights = Weights::default();
+
+        let unlocked = artifact(0, false, &substats);
+        assert!(is_fodder(
+            &unlocked,
+            &Metrics::new(&unlocked, &weights),
+            0.3
+        ));
+
+        let locked = artifact(0, true, &substats);
+        assert!(!is_fodder(&locked, &Metrics::new(&locked, &weights), 0.3));
+
+        let crit = artifact(
+            0,
+            false,
+            &[
+                (StatKey::CritRate, 3.5),
+                (StatKey::CritDmg, 7.0),
+                (StatKey::Hp, 209.13),
+            ],
+        );
+        assert!(!is_fodder(&crit, &Metrics::new(&crit, &weights), 0.3));
+    }
+
+    #[test]
+    fn counts_group_by_set_slot_and_main_stat() {
+        let artifacts = [artifact(0, false, &[]), artifact(4, false, &[])];
+        let counts = Counts::new(&artifacts);
+        assert_eq!(counts.sets[&SetKey::GladiatorsFinale], 2);
+        assert_eq!(counts.slots[&SlotKey::Flower], 2);
+        assert_eq!(counts.main_stats[&StatKey::Hp], 2);
+    }
+}
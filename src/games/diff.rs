@@ -0,0 +1,224 @@
+use crate::games::{Artifact, Substat};
+
+pub struct Upgrade {
+    pub before: Artifact,
+    pub after: Artifact,
+    // Substat increases, including lines added by the upgrade.
+    pub gained: Vec<Substat>,
+}
+
+#[derive(Default)]
+pub struct ArtifactDiff {
+    pub new: Vec<Artifact>,
+    // Fed, salvaged or otherwise gone since the previous snapshot.
+    pub removed: Vec<Artifact>,
+    pub upgraded: Vec<Upgrade>,
+}
+
+fn same_piece(a: &Artifact, b: &Artifact) -> bool {
+    a.setKey == b.setKey
+        && a.slotKey == b.slotKey
+        && a.rarity == b.rarity
+        && a.mainStatKey == b.mainStatKey
+}
+
+// Locking doesn't change an artifact, so it is ignored.
+fn unchanged(before: &Artifact, after: &Artifact) -> bool {
+    same_piece(before, after) && before.level == after.level && before.substats == after.substats
+}
+
+// Upgrading only raises the level, increases existing lines in place and
+// appends new ones.
+fn upgrade_of(before: &Artifact, after: &Artifact) -> Option<Vec<Substat>> {
+    if !same_piece(before, after)
+        || after.level <= before.level
+        || after.substats.len() < before.substats.len()
+    {
+        return None;
+    }
+
+    let mut gained = Vec::new();
+    for (i, substat) in after.substats.iter().enumerate() {
+        let previous = match before.substats.get(i) {
+            Some(previous) if previous.key != substat.key || previous.value > substat.value => {
+                return None;
+            }
+            Some(previous) => previous.value,
+            None => 0.0,
+        };
+
+        if substat.value > previous {
+            gained.push(Substat {
+                key: substat.key,
+                // Values are already rounded for display, keep the difference that way.
+                value: ((substat.value - previous) * 10.0).round() / 10.0,
+            });
+        }
+    }
+
+    Some(gained)
+}
+
+// Captured artifacts carry no stable id, so pieces are matched by content:
+// first identical ones, then the closest possible upgrade. Returns the index
+// of the matching previous artifact for every current one.
+pub fn matches(previous: &[Artifact], current: &[Artifact]) -> Vec<Option<usize>> {
+    let mut matched: Vec<Option<usize>> = vec![None; current.len()];
+    let mut used = vec![false; previous.len()];
+
+    for (p, before) in previous.iter().enumerate() {
+        if let Some(c) =
+            (0..current.len()).find(|c| matched[*c].is_none() && unchanged(before, &current[*c]))
+        {
+            matched[c] = Some(p);
+            used[p] = true;
+        }
+    }
+
+    for (p, before) in previous.iter().enumerate() {
+        if used[p] {
+            continue;
+        }
+
+        if let Some(c) = (0..current.len())
+            .filter(|c| matched[*c].is_none() && upgrade_of(before, &current[*c]).is_some())
+            .min_by_key(|c| current[*c].level)
+        {
+            matched[c] = Some(p);
+            used[p] = true;
+        }
+    }
+
+    matched
+}
+
+pub fn artifacts(previous: &[Artifact], current: &[Artifact]) -> ArtifactDiff {
+    let matched = matches(previous, current);
+
+    let mut diff = ArtifactDiff::default();
+    for (after, before) in current.iter().zip(&matched) {
+        let Some(before) = before.map(|p| &previous[p]) else {
+            diff.new.push(after.clone());
+            continue;
+        };
+
+        if before.level != after.level
+            && let Some(gained) = upgrade_of(before, after)
+        {
+            diff.upgraded.push(Upgrade {
+                before: before.clone(),
+                after: after.clone(),
+                gained,
+            });
+        }
+    }
+
+    diff.removed = (0..previous.len())
+        .filter(|p| !matched.contains(&Some(*p)))
+        .map(|p| previous[p].clone())
+        .collect();
+    diff
+}
+
+pub fn achievements(previous: &[u32], current: &[u32]) -> Vec<u32> {
+    current
+        .iter()
+        .filter(|id| !previous.contains(id))
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::{SetKey, SlotKey, StatKey};
+
+    fn artifact(level: u32, substats: &[(StatKey, f64)]) -> Artifact {
+        Artifact {
+            setKey: SetKey::EmblemOfSeveredFate,
+            slotKey: SlotKey::Sands,
+            level,
+            rarity: 5,
+            mainStatKey: StatKey::EnerRech,
+            lock: false,
+            substats: substats
+                .iter()
+                .map(|(key, value)| Substat {
+                    key: *key,
+                    value: *value,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn detects_new_removed_and_upgraded_artifacts() {
+        let kept = artifact(20, &[(StatKey::CritRate, 10.5)]);
+        let fed = artifact(
+            0,
+            &[
+                (StatKey::Hp, 209.0),
+                (StatKey::Def, 16.0),
+                (StatKey::Atk, 14.0),
+            ],
+        );
+        let before = artifact(
+            0,
+            &[
+                (StatKey::CritRate, 3.9),
+                (StatKey::CritDmg, 7.0),
+                (StatKey::EleMas, 19.0),
+            ],
+        );
+        let after = artifact(
+            8,
+            &[
+                (StatKey::CritRate, 7.4),
+                (StatKey::CritDmg, 7.0),
+                (StatKey::EleMas, 19.0),
+                (StatKey::AtkPercent, 5.8),
+            ],
+        );
+        let mut relocked = kept.clone();
+        relocked.lock = true;
+        let dropped = artifact(0, &[(StatKey::Def, 23.0)]);
+
+        let diff = artifacts(
+            &[kept, fed.clone(), before],
+            &[relocked, after, dropped.clone()],
+        );
+
+        assert_eq!(diff.new, [dropped]);
+        assert_eq!(diff.removed, [fed]);
+        assert_eq!(diff.upgraded.len(), 1);
+        assert_eq!(
+            diff.upgraded[0].gained,
+            [
+                Substat {
+                    key: StatKey::CritRate,
+                    value: 3.5
+                },
+                Substat {
+                    key: StatKey::AtkPercent,
+                    value: 5.8
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_different_roll_history_is_not_an_upgrade() {
+        let before = artifact(0, &[(StatKey::CritRate, 3.9), (StatKey::CritDmg, 7.0)]);
+        let other = artifact(4, &[(StatKey::CritDmg, 14.0), (StatKey::CritRate, 3.9)]);
+
+        let diff = artifacts(std::slice::from_ref(&before), std::slice::from_ref(&other));
+        assert!(diff.upgraded.is_empty());
+        assert_eq!(diff.removed, [before]);
+        assert_eq!(diff.new, [other]);
+    }
+
+    #[test]
+    fn lists_newly_completed_achievements() {
+        assert_eq!(achievements(&[1, 2], &[2, 1, 3]), [3]);
+    }
+}
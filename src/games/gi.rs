@@ -6,28 +6,42 @@ use std::{
     sync::mpsc,
 };
 
-use auto_artifactarium::{matches_achievement_packet, matches_artifact_packet, GamePacket, GameSniffer};
+use auto_artifactarium::{
+    GamePacket, GameSniffer, matches_achievement_packet, matches_artifact_packet,
+};
 use base64::prelude::*;
 
 use regex::Regex;
 
+use super::Progress;
+use crate::data::Tables;
+
 pub fn sniff(
     achievement_ids: &[u32],
     device_rx: &mpsc::Receiver<Vec<u8>>,
+    progress: &impl Fn(Progress),
 ) -> anyhow::Result<Vec<u32>> {
     let keys = load_keys()?;
     let mut sniffer = GameSniffer::new().set_initial_keys(keys);
 
     let mut achievements = Vec::new();
 
+    let mut connected = false;
+
     while let Ok(data) = device_rx.recv() {
         let Some(GamePacket::Commands(commands)) = sniffer.receive_packet(data) else {
             continue;
         };
 
+        if !connected {
+            connected = true;
+            progress(Progress::Handshake);
+        }
+
         for command in commands {
             if let Some(read_achievements) = matches_achievement_packet(&command) {
                 tracing::info!("Found achievement packet");
+                progress(Progress::CommandMatched("achievements"));
 
                 if !achievements.is_empty() {
                     continue;
@@ -55,83 +69,83 @@ pub fn sniff(
     Ok(achievements)
 }
 
-#[derive(serde::Serialize)]
-#[allow(non_snake_case)]
-pub struct Artifact {
-    setKey: String,
-    slotKey: String,
-    level: u32,
-    rarity: u32,
-    mainStatKey: String,
-    lock: bool,
-    substats: Vec<super::Substat>
-}
-
 pub fn sniff_artifacts(
-    artifact_id_map: &HashMap<u32, super::ArtifactData>,
-    main_prop_map: &HashMap<u32, String>,
-    affix_prop_map: &HashMap<u32, super::Substat>,
+    tables: &Tables,
     device_rx: &mpsc::Receiver<Vec<u8>>,
-) -> anyhow::Result<Vec<Artifact>> {
+    progress: &impl Fn(Progress),
+) -> anyhow::Result<(Vec<super::Artifact>, Vec<String>)> {
     let keys = load_keys()?;
     let mut sniffer = GameSniffer::new().set_initial_keys(keys);
 
     let mut artifacts = Vec::new();
+    let mut warnings = Vec::new();
+
+    let mut connected = false;
 
     while let Ok(data) = device_rx.recv() {
         let Some(GamePacket::Commands(commands)) = sniffer.receive_packet(data) else {
             continue;
         };
 
+        if !connected {
+            connected = true;
+            progress(Progress::Handshake);
+        }
+
         for command in commands {
             if let Some(read_artifacts) = matches_artifact_packet(&command) {
                 tracing::info!("Found artifact packet");
+                progress(Progress::CommandMatched("artifacts"));
 
                 if !artifacts.is_empty() {
                     continue;
                 }
 
                 for artifact in read_artifacts {
-                    if let Some(artifact_type) = artifact_id_map.get(&artifact.id) {
-                        let mut substats = Vec::<super::Substat>::new();
-                        for substat_id in artifact.append_prop_id_list {
-                            if let Some(current_substat) = affix_prop_map.get(&substat_id) {
-                                let mut found = false;
-                                for substat in substats.iter_mut() {
-                                    if substat.key == current_substat.key {
-                                        substat.value += current_substat.value;
-                                        found = true;
-                                        break;
-                                    }
-                                }
-
-                                if !found {
-                                    substats.push(current_substat.clone());
-                                }
-                            }
-                        }
-
-                        for substat in substats.iter_mut() {
-                            if substat.key.ends_with("_") {
-                                substat.value = ((substat.value * 100.0).round() / 10.0).round() / 10.0;
-                            }
-                            else {
-                                substat.value = substat.value.round();
-                            }
+                    let Some(artifact_type) = tables.artifacts.get(&artifact.id) else {
+                        warnings.push(format!("Skipped artifact {}: unknown item id", artifact.id));
+                        continue;
+                    };
+
+                    let Some(set_key) = tables.set_keys.get(&artifact_type.setId) else {
+                        warnings.push(format!(
+                            "Skipped artifact {}: unknown set {}",
+                            artifact.id, artifact_type.setId
+                        ));
+                        continue;
+                    };
+
+                    let Some(main_stat_key) = tables.main_props.get(&artifact.main_prop_id) else {
+                        warnings.push(format!(
+                            "Skipped artifact {}: unknown main stat {}",
+                            artifact.id, artifact.main_prop_id
+                        ));
+                        continue;
+                    };
+
+                    let substats = match super::substats::reconstruct(
+                        &artifact.append_prop_id_list,
+                        &tables.affixes,
+                    ) {
+                        Ok(substats) => substats,
+                        Err(affix_id) => {
+                            warnings.push(format!(
+                                "Skipped artifact {}: unknown substat {affix_id}",
+                                artifact.id
+                            ));
+                            continue;
                         }
-
-                        artifacts.push(
-                            Artifact{
-                                setKey: artifact_type.setKey.clone(),
-                                slotKey: artifact_type.slotKey.clone(),
-                                level: artifact.level - 1,
-                                rarity: artifact_type.rarity,
-                                mainStatKey: main_prop_map.get(&artifact.main_prop_id).cloned().unwrap_or_else(|| "null".to_string()),
-                                lock: artifact.is_locked,
-                                substats: substats
-                            }
-                        );
-                    }
+                    };
+
+                    artifacts.push(super::Artifact {
+                        setKey: *set_key,
+                        slotKey: artifact_type.slotKey,
+                        level: artifact.level - 1,
+                        rarity: artifact_type.rarity,
+                        mainStatKey: *main_stat_key,
+                        lock: artifact.is_locked,
+                        substats,
+                    });
                 }
             }
         }
@@ -145,7 +159,11 @@ pub fn sniff_artifacts(
         return Err(anyhow::anyhow!("No artifacts found"));
     }
 
-    Ok(artifacts)
+    for warning in &warnings {
+        tracing::warn!("{warning}");
+    }
+
+    Ok((artifacts, warnings))
 }
 
 fn load_keys() -> anyhow::Result<HashMap<u16, Vec<u8>>> {
@@ -160,25 +178,39 @@ fn load_keys() -> anyhow::Result<HashMap<u16, Vec<u8>>> {
     Ok(keys_bytes)
 }
 
-pub fn game_path() -> anyhow::Result<PathBuf> {
-    let mut log_path = PathBuf::from(&std::env::var("APPDATA")?);
-    log_path.pop();
-    log_path.push("LocalLow");
-    log_path.push("miHoYo");
-
-    let mut log_path_cn = log_path.clone();
+// The folder the game writes its logs to, global or CN release.
+fn log_dir() -> anyhow::Result<PathBuf> {
+    let mut dir = PathBuf::from(&std::env::var("APPDATA")?);
+    dir.pop();
+    dir.push("LocalLow");
+    dir.push("miHoYo");
+
+    let dir_cn = dir.join("原神");
+    dir.push("Genshin Impact");
+
+    match (
+        dir.join("output_log.txt").exists(),
+        dir_cn.join("output_log.txt").exists(),
+    ) {
+        (true, _) => Ok(dir),
+        (_, true) => Ok(dir_cn),
+        _ => Err(anyhow::anyhow!("Can't find log file")),
+    }
+}
 
-    log_path.push("Genshin Impact");
-    log_path_cn.push("原神");
+// The UID of the last login, which the game writes next to its log.
+pub fn uid() -> anyhow::Result<String> {
+    let path = log_dir()?.join("UidInfo.txt");
+    let uid = std::fs::read_to_string(&path)?.trim().to_string();
+    if uid.is_empty() || !uid.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow::anyhow!("No UID in {}", path.display()));
+    }
 
-    log_path.push("output_log.txt");
-    log_path_cn.push("output_log.txt");
+    Ok(uid)
+}
 
-    let log_path = match (log_path.exists(), log_path_cn.exists()) {
-        (true, _) => log_path,
-        (_, true) => log_path_cn,
-        _ => return Err(anyhow::anyhow!("Can't find log file")),
-    };
+pub fn game_path() -> anyhow::Result<PathBuf> {
+    let log_path = log_dir()?.join("output_log.txt");
 
     let re = Regex::new(r".:\\.+(GenshinImpact_Data|YuanShen_Data)")?;
 
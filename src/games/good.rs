@@ -0,0 +1,264 @@
+use std::{collections::BTreeSet, sync::Mutex};
+
+use serde::de::Error;
+
+macro_rules! good_key {
+    ($name:ident { $($variant:ident => $key:literal,)* } $(other $other:ident)?) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum $name {
+            $($variant,)*
+            $($other(&'static str),)?
+        }
+
+        impl $name {
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $($name::$variant => $key,)*
+                    $($name::$other(key) => key,)?
+                }
+            }
+
+            pub fn from_good(key: &str) -> Option<Self> {
+                match key {
+                    $($key => Some($name::$variant),)*
+                    $(key if is_good_identifier(key) => Some($name::$other(intern(key))),)?
+                    _ => None,
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let key = String::deserialize(deserializer)?;
+                Self::from_good(&key).ok_or_else(|| {
+                    D::Error::custom(format!("unknown {} \"{key}\"", stringify!($name)))
+                })
+            }
+        }
+    };
+}
+
+// GOOD set keys are PascalCase and strictly alphanumeric.
+fn is_good_identifier(key: &str) -> bool {
+    key.starts_with(|c: char| c.is_ascii_uppercase())
+        && key.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+// Keys of sets released after this build only come from the data tables and
+// the override file, so leaking each distinct one once is bounded.
+fn intern(key: &str) -> &'static str {
+    static KEYS: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+    let mut keys = KEYS.lock().unwrap();
+    if let Some(key) = keys.get(key) {
+        return key;
+    }
+
+    let key: &'static str = Box::leak(key.to_string().into_boxed_str());
+    keys.insert(key);
+    key
+}
+
+good_key!(SlotKey {
+    Flower => "flower",
+    Plume => "plume",
+    Sands => "sands",
+    Goblet => "goblet",
+    Circlet => "circlet",
+});
+
+impl SlotKey {
+    pub fn from_equip_type(equip_type: &str) -> Option<Self> {
+        Some(match equip_type {
+            "EQUIP_BRACER" => SlotKey::Flower,
+            "EQUIP_NECKLACE" => SlotKey::Plume,
+            "EQUIP_SHOES" => SlotKey::Sands,
+            "EQUIP_RING" => SlotKey::Goblet,
+            "EQUIP_DRESS" => SlotKey::Circlet,
+            _ => return None,
+        })
+    }
+}
+
+good_key!(StatKey {
+    Hp => "hp",
+    HpPercent => "hp_",
+    Atk => "atk",
+    AtkPercent => "atk_",
+    Def => "def",
+    DefPercent => "def_",
+    EleMas => "eleMas",
+    EnerRech => "enerRech_",
+    Heal => "heal_",
+    CritRate => "critRate_",
+    CritDmg => "critDMG_",
+    PhysicalDmg => "physical_dmg_",
+    AnemoDmg => "anemo_dmg_",
+    GeoDmg => "geo_dmg_",
+    ElectroDmg => "electro_dmg_",
+    HydroDmg => "hydro_dmg_",
+    PyroDmg => "pyro_dmg_",
+    CryoDmg => "cryo_dmg_",
+    DendroDmg => "dendro_dmg_",
+});
+
+impl StatKey {
+    pub fn from_fight_prop(prop: &str) -> Option<Self> {
+        Some(match prop {
+            "FIGHT_PROP_HP" => StatKey::Hp,
+            "FIGHT_PROP_HP_PERCENT" => StatKey::HpPercent,
+            "FIGHT_PROP_ATTACK" => StatKey::Atk,
+            "FIGHT_PROP_ATTACK_PERCENT" => StatKey::AtkPercent,
+            "FIGHT_PROP_DEFENSE" => StatKey::Def,
+            "FIGHT_PROP_DEFENSE_PERCENT" => StatKey::DefPercent,
+            "FIGHT_PROP_ELEMENT_MASTERY" => StatKey::EleMas,
+            "FIGHT_PROP_CHARGE_EFFICIENCY" => StatKey::EnerRech,
+            "FIGHT_PROP_HEAL_ADD" => StatKey::Heal,
+            "FIGHT_PROP_CRITICAL" => StatKey::CritRate,
+            "FIGHT_PROP_CRITICAL_HURT" => StatKey::CritDmg,
+            "FIGHT_PROP_PHYSICAL_ADD_HURT" => StatKey::PhysicalDmg,
+            "FIGHT_PROP_WIND_ADD_HURT" => StatKey::AnemoDmg,
+            "FIGHT_PROP_ROCK_ADD_HURT" => StatKey::GeoDmg,
+            "FIGHT_PROP_ELEC_ADD_HURT" => StatKey::ElectroDmg,
+            "FIGHT_PROP_WATER_ADD_HURT" => StatKey::HydroDmg,
+            "FIGHT_PROP_FIRE_ADD_HURT" => StatKey::PyroDmg,
+            "FIGHT_PROP_ICE_ADD_HURT" => StatKey::CryoDmg,
+            "FIGHT_PROP_GRASS_ADD_HURT" => StatKey::DendroDmg,
+            _ => return None,
+        })
+    }
+
+    pub fn is_percent(self) -> bool {
+        self.as_str().ends_with('_')
+    }
+}
+
+good_key!(SetKey {
+    Adventurer => "Adventurer",
+    ArchaicPetra => "ArchaicPetra",
+    Berserker => "Berserker",
+    BlizzardStrayer => "BlizzardStrayer",
+    BloodstainedChivalry => "BloodstainedChivalry",
+    BraveHeart => "BraveHeart",
+    CrimsonWitchOfFlames => "CrimsonWitchOfFlames",
+    DeepwoodMemories => "DeepwoodMemories",
+    DefendersWill => "DefendersWill",
+    DesertPavilionChronicle => "DesertPavilionChronicle",
+    EchoesOfAnOffering => "EchoesOfAnOffering",
+    EmblemOfSeveredFate => "EmblemOfSeveredFate",
+    FlowerOfParadiseLost => "FlowerOfParadiseLost",
+    FragmentOfHarmonicWhimsy => "FragmentOfHarmonicWhimsy",
+    Gambler => "Gambler",
+    GildedDreams => "GildedDreams",
+    GladiatorsFinale => "GladiatorsFinale",
+    GoldenTroupe => "GoldenTroupe",
+    HeartOfDepth => "HeartOfDepth",
+    HuskOfOpulentDreams => "HuskOfOpulentDreams",
+    Instructor => "Instructor",
+    Lavawalker => "Lavawalker",
+    LuckyDog => "LuckyDog",
+    MaidenBeloved => "MaidenBeloved",
+    MarechausseeHunter => "MarechausseeHunter",
+    MartialArtist => "MartialArtist",
+    NighttimeWhispersInTheEchoingWoods => "NighttimeWhispersInTheEchoingWoods",
+    NoblesseOblige => "NoblesseOblige",
+    NymphsDream => "NymphsDream",
+    ObsidianCodex => "ObsidianCodex",
+    OceanHuedClam => "OceanHuedClam",
+    PaleFlame => "PaleFlame",
+    PrayersForDestiny => "PrayersForDestiny",
+    PrayersForIllumination => "PrayersForIllumination",
+    PrayersForWisdom => "PrayersForWisdom",
+    PrayersToSpringtime => "PrayersToSpringtime",
+    ResolutionOfSojourner => "ResolutionOfSojourner",
+    RetracingBolide => "RetracingBolide",
+    Scholar => "Scholar",
+    ScrollOfTheHeroOfCinderCity => "ScrollOfTheHeroOfCinderCity",
+    ShimenawasReminiscence => "ShimenawasReminiscence",
+    SongOfDaysPast => "SongOfDaysPast",
+    TenacityOfTheMillelith => "TenacityOfTheMillelith",
+    TheExile => "TheExile",
+    ThunderingFury => "ThunderingFury",
+    Thundersoother => "Thundersoother",
+    TinyMiracle => "TinyMiracle",
+    TravelingDoctor => "TravelingDoctor",
+    UnfinishedReverie => "UnfinishedReverie",
+    VermillionHereafter => "VermillionHereafter",
+    ViridescentVenerer => "ViridescentVenerer",
+    VourukashasGlow => "VourukashasGlow",
+    WanderersTroupe => "WanderersTroupe",
+    LongNightsOath => "LongNightsOath",
+    FinaleOfTheDeepGalleries => "FinaleOfTheDeepGalleries",
+    NightOfTheSkysUnveiling => "NightOfTheSkysUnveiling",
+    SilkenMoonsSerenade => "SilkenMoonsSerenade",
+    AubadeOfMorningstarAndMoon => "AubadeOfMorningstarAndMoon",
+    ADayCarvedFromRisingWinds => "ADayCarvedFromRisingWinds",
+} other Other);
+
+impl SetKey {
+    pub fn is_known(self) -> bool {
+        !matches!(self, SetKey::Other(_))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Substat {
+    pub key: StatKey,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[allow(non_snake_case)]
+pub struct Artifact {
+    pub setKey: SetKey,
+    pub slotKey: SlotKey,
+    pub level: u32,
+    pub rarity: u32,
+    pub mainStatKey: StatKey,
+    pub lock: bool,
+    pub substats: Vec<Substat>,
+}
+
+// None for rarities artifacts don't come in.
+pub fn max_level(rarity: u32) -> Option<u32> {
+    Some(match rarity {
+        5 => 20,
+        4 => 16,
+        3 => 12,
+        1 | 2 => 4,
+        _ => return None,
+    })
+}
+
+pub const SOURCE: &str = "stardb-exporter";
+
+#[derive(serde::Serialize)]
+pub struct Good<'a> {
+    format: &'a str,
+    version: u32,
+    source: &'a str,
+    artifacts: &'a [Artifact],
+}
+
+impl<'a> Good<'a> {
+    pub fn new(artifacts: &'a [Artifact]) -> Self {
+        Self {
+            format: "GOOD",
+            version: 2,
+            source: SOURCE,
+            artifacts,
+        }
+    }
+}
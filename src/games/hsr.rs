@@ -9,9 +9,12 @@ use std::{
 use auto_reliquary::{GamePacket, GameSniffer, matches_achievement_packet};
 use base64::prelude::*;
 
+use super::Progress;
+
 pub fn sniff(
     achievement_ids: &[u32],
     device_rx: &mpsc::Receiver<Vec<u8>>,
+    progress: &impl Fn(Progress),
 ) -> anyhow::Result<Vec<u32>> {
     let keys = load_keys()?;
 
@@ -19,14 +22,22 @@ pub fn sniff(
 
     let mut achievements = Vec::new();
 
+    let mut connected = false;
+
     while let Ok(data) = device_rx.recv() {
         let Some(GamePacket::Commands(commands)) = sniffer.receive_packet(data) else {
             continue;
         };
 
+        if !connected {
+            connected = true;
+            progress(Progress::Handshake);
+        }
+
         for command in commands {
             if let Some(read_achievements) = matches_achievement_packet(&command) {
                 tracing::info!("Found achievement packet");
+                progress(Progress::CommandMatched("achievements"));
 
                 if !achievements.is_empty() {
                     continue;
@@ -0,0 +1,137 @@
+use serde_json::Value;
+
+use super::diff;
+use super::{Artifact, good};
+
+pub struct Merged {
+    pub database: Value,
+    // Captured artifacts that kept the character they were equipped on.
+    pub located: usize,
+}
+
+// Parses the artifacts of a GOOD database, keeping their position so that
+// their extra fields can be looked up. Optimizers pad substats with empty
+// keys, and artifacts this exporter can't represent are left unmatched.
+fn parse_artifacts(database: &Value) -> Vec<(usize, Artifact)> {
+    let Some(artifacts) = database.get("artifacts").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+
+    artifacts
+        .iter()
+        .enumerate()
+        .filter_map(|(i, artifact)| {
+            let mut artifact = artifact.clone();
+            if let Some(substats) = artifact.get_mut("substats").and_then(Value::as_array_mut) {
+                substats.retain(|s| {
+                    s.get("key")
+                        .and_then(Value::as_str)
+                        .is_some_and(|k| !k.is_empty())
+                });
+            }
+            Some((i, serde_json::from_value(artifact).ok()?))
+        })
+        .collect()
+}
+
+// Replaces the artifacts of a GOOD database with the captured ones and keeps
+// everything else, like characters, weapons and optimizer settings.
+pub fn merge(mut database: Value, artifacts: &[Artifact]) -> anyhow::Result<Merged> {
+    if database.get("format").and_then(Value::as_str) != Some("GOOD") {
+        return Err(anyhow::anyhow!("Not a GOOD file"));
+    }
+
+    let existing = parse_artifacts(&database);
+    let previous: Vec<Artifact> = existing.iter().map(|(_, a)| a.clone()).collect();
+    let matched = diff::matches(&previous, artifacts);
+
+    let mut located = 0;
+    let mut merged = Vec::new();
+    for (artifact, previous) in artifacts.iter().zip(matched) {
+        let mut value = serde_json::to_value(artifact)?;
+
+        let location = previous
+            .and_then(|p| database["artifacts"][existing[p].0].get("location"))
+            .filter(|l| l.as_str().is_some_and(|l| !l.is_empty()))
+            .cloned();
+        if let Some(location) = location {
+            value["location"] = location;
+            located += 1;
+        }
+
+        merged.push(value);
+    }
+
+    database["source"] = Value::from(good::SOURCE);
+    database["artifacts"] = Value::Array(merged);
+
+    Ok(Merged { database, located })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn captured() -> Vec<Artifact> {
+        serde_json::from_value(json!([
+            {
+                "setKey": "EmblemOfSeveredFate", "slotKey": "sands", "level": 20, "rarity": 5,
+                "mainStatKey": "enerRech_", "lock": true,
+                "substats": [{ "key": "critRate_", "value": 10.5 }, { "key": "critDMG_", "value": 7.0 }]
+            },
+            {
+                "setKey": "GladiatorsFinale", "slotKey": "flower", "level": 0, "rarity": 5,
+                "mainStatKey": "hp", "lock": false,
+                "substats": [{ "key": "atk", "value": 19.0 }]
+            }
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn keeps_everything_but_the_artifacts_and_preserves_locations() {
+        let database = json!({
+            "format": "GOOD",
+            "version": 2,
+            "source": "GO",
+            "characters": [{ "key": "Raiden", "level": 90 }],
+            "weapons": [{ "key": "EngulfingLightning", "location": "Raiden" }],
+            "artifacts": [
+                {
+                    "setKey": "EmblemOfSeveredFate", "slotKey": "sands", "level": 16, "rarity": 5,
+                    "mainStatKey": "enerRech_", "location": "Raiden", "lock": true,
+                    "substats": [
+                        { "key": "critRate_", "value": 7.0 },
+                        { "key": "critDMG_", "value": 7.0 },
+                        { "key": "", "value": 0 }
+                    ]
+                },
+                {
+                    "setKey": "NoblesseOblige", "slotKey": "plume", "level": 20, "rarity": 5,
+                    "mainStatKey": "atk", "location": "Bennett", "lock": true, "substats": []
+                }
+            ]
+        });
+
+        let merged = merge(database, &captured()).unwrap();
+        let database = merged.database;
+
+        assert_eq!(merged.located, 1);
+        assert_eq!(database["characters"][0]["key"], "Raiden");
+        assert_eq!(database["weapons"][0]["location"], "Raiden");
+        assert_eq!(database["source"], "stardb-exporter");
+
+        let artifacts = database["artifacts"].as_array().unwrap();
+        assert_eq!(artifacts.len(), 2);
+        assert_eq!(artifacts[0]["location"], "Raiden");
+        assert_eq!(artifacts[0]["level"], 20);
+        assert!(artifacts[1].get("location").is_none());
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_good() {
+        assert!(merge(json!({ "artifacts": [] }), &captured()).is_err());
+    }
+}
@@ -1,15 +1,39 @@
+pub mod analytics;
+pub mod diff;
 mod gi;
+mod good;
 mod hsr;
+pub mod merge;
+mod session;
+mod substats;
+pub mod validate;
 mod zzz;
 
 use std::{
-    collections::HashMap, path::{Path, PathBuf}, sync::mpsc, thread
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::mpsc,
 };
 
-use crate::app::{Message, State};
+use crate::{
+    data::{Language, Tables},
+    stardb,
+};
 use regex::Regex;
 
-#[derive(Clone, Copy, PartialEq)]
+// Reported by the capture threads while waiting for the game.
+#[derive(Clone, Copy)]
+pub enum Progress {
+    DeviceReady(usize),
+    DeviceRestarting(usize),
+    // The first packets of the game could be decrypted.
+    Handshake,
+    // The packet with the data showed up, with the kind of data.
+    CommandMatched(&'static str),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Game {
     Hsr,
     Gi,
@@ -17,125 +41,53 @@ pub enum Game {
 }
 
 impl Game {
-    pub fn achievements(self, message_tx: &mpsc::Sender<Message>) {
-        let message_tx = message_tx.clone();
-
-        thread::spawn(move || {
-            let achievement_ids = match self.achievement_ids() {
-                Ok(achievement_ids) => achievement_ids,
-                Err(e) => {
-                    message_tx
-                        .send(Message::GoTo(State::Error(e.to_string())))
-                        .unwrap();
-                    return;
-                }
-            };
-
-            let devices = match self.devices() {
-                Ok(devices) => devices,
-                Err(e) => {
-                    message_tx
-                        .send(Message::GoTo(State::Error(e.to_string())))
-                        .unwrap();
-                    return;
-                }
-            };
-
-            let (device_tx, device_rx) = mpsc::channel();
-            for (i, device) in devices.into_iter().enumerate() {
-                let device_tx = device_tx.clone();
-                let message_tx = message_tx.clone();
-                std::thread::spawn(move || self.capture_device(i, device, &device_tx, &message_tx));
-            }
+    pub fn id(self) -> &'static str {
+        match self {
+            Game::Hsr => "hsr",
+            Game::Gi => "gi",
+            Game::Zzz => "zzz",
+        }
+    }
 
-            let achievements = match self {
-                Game::Hsr => hsr::sniff(&achievement_ids, &device_rx),
-                Game::Gi => gi::sniff(&achievement_ids, &device_rx),
-                _ => unimplemented!(),
-            };
-            let achievements = match achievements {
-                Ok(achievements) => achievements,
-                Err(e) => {
-                    message_tx
-                        .send(Message::GoTo(State::Error(e.to_string())))
-                        .unwrap();
-                    return;
-                }
-            };
+    // Blocks until the game sends the achievements, together with their titles.
+    fn capture_achievements(
+        self,
+        language: Language,
+        progress: impl Fn(Progress) + Clone + Send + 'static,
+    ) -> anyhow::Result<Achievements> {
+        let names = self.achievement_names(language)?;
+        let device_rx = self.capture_devices(progress.clone())?;
+
+        let achievement_ids: Vec<_> = names.keys().copied().collect();
+        let achievements = match self {
+            Game::Hsr => hsr::sniff(&achievement_ids, &device_rx, &progress)?,
+            Game::Gi => gi::sniff(&achievement_ids, &device_rx, &progress)?,
+            _ => unimplemented!(),
+        };
 
-            message_tx
-                .send(Message::GoTo(State::Achievements(achievements)))
-                .unwrap();
-        });
+        Ok(Achievements {
+            completed: achievements,
+            names,
+        })
     }
 
-    pub fn artifacts(self, message_tx: &mpsc::Sender<Message>) {
-        let message_tx = message_tx.clone();
-
-        thread::spawn(move || {
-            let artifact_id_map = match build_artifact_id_map() {
-                Ok(artifact_id_map) => artifact_id_map,
-                Err(e) => {
-                    message_tx
-                        .send(Message::GoTo(State::Error(e.to_string())))
-                        .unwrap();
-                    return;
-                }
-            };
-            let main_prop_map = match build_main_prop_map() {
-                Ok(main_prop_map) => main_prop_map,
-                Err(e) => {
-                    message_tx
-                        .send(Message::GoTo(State::Error(e.to_string())))
-                        .unwrap();
-                    return;
-                }
-            };
-            let affix_prop_map = match build_affix_prop_map() {
-                Ok(affix_prop_map) => affix_prop_map,
-                Err(e) => {
-                    message_tx
-                        .send(Message::GoTo(State::Error(e.to_string())))
-                        .unwrap();
-                    return;
-                }
-            };
-
-            let devices = match self.devices() {
-                Ok(devices) => devices,
-                Err(e) => {
-                    message_tx
-                        .send(Message::GoTo(State::Error(e.to_string())))
-                        .unwrap();
-                    return;
-                }
-            };
-
-            let (device_tx, device_rx) = mpsc::channel();
-            for (i, device) in devices.into_iter().enumerate() {
-                let device_tx = device_tx.clone();
-                let message_tx = message_tx.clone();
-                std::thread::spawn(move || self.capture_device(i, device, &device_tx, &message_tx));
-            }
+    // Blocks until the game sends the artifacts, with warnings for skipped ones.
+    fn capture_artifacts(
+        self,
+        tables: &Tables,
+        progress: impl Fn(Progress) + Clone + Send + 'static,
+    ) -> anyhow::Result<Artifacts> {
+        let device_rx = self.capture_devices(progress.clone())?;
 
-            let artifacts = match self {
-                Game::Gi => gi::sniff_artifacts(&artifact_id_map, &main_prop_map, &affix_prop_map, &device_rx),
-                _ => unimplemented!(),
-            };
-            let artifacts = match artifacts {
-                Ok(artifacts) => artifacts,
-                Err(e) => {
-                    message_tx
-                        .send(Message::GoTo(State::Error(e.to_string())))
-                        .unwrap();
-                    return;
-                }
-            };
+        let (artifacts, warnings) = match self {
+            Game::Gi => gi::sniff_artifacts(tables, &device_rx, &progress)?,
+            _ => unimplemented!(),
+        };
 
-            message_tx
-                .send(Message::GoTo(State::Artifacts(artifacts)))
-                .unwrap();
-        });
+        Ok(Artifacts {
+            artifacts,
+            warnings,
+        })
     }
 
     pub fn game_path(self) -> anyhow::Result<PathBuf> {
@@ -146,6 +98,25 @@ impl Game {
         }
     }
 
+    // The UID the game was last logged in with, for games that leave it on disk.
+    pub fn uid(self) -> Option<String> {
+        match self {
+            Game::Gi => gi::uid()
+                .inspect_err(|e| tracing::debug!("No UID from the game: {e}"))
+                .ok(),
+            _ => None,
+        }
+    }
+
+    // The gacha log url from the web cache of the game, looking the game up
+    // when no data dir is given.
+    pub fn locate_gacha_url(self, path: Option<&Path>) -> anyhow::Result<String> {
+        match path {
+            Some(path) => pulls_from_game_path(path),
+            None => pulls_from_game_path(&self.game_path()?),
+        }
+    }
+
     pub fn achievement_url(self) -> String {
         let prefix = match self {
             Game::Hsr => "",
@@ -166,22 +137,12 @@ impl Game {
         format!("https://stardb.gg/{path}")
     }
 
-    fn achievement_ids(self) -> anyhow::Result<Vec<u32>> {
-        #[derive(serde::Deserialize)]
-        struct Achievement {
-            id: u32,
-        }
+    // Titles are only displayed, the exported ids are the same in every language.
+    pub fn achievement_names(self, language: Language) -> anyhow::Result<BTreeMap<u32, String>> {
+        let achievements = stardb::Client::default().achievements(self, language.stardb())?;
+        let names = achievements.into_iter().map(|a| (a.id, a.name)).collect();
 
-        let url = match self {
-            Game::Hsr => "https://stardb.gg/api/achievements",
-            Game::Gi => "https://stardb.gg/api/gi/achievements",
-            _ => unimplemented!(),
-        };
-
-        let achievements: Vec<Achievement> = ureq::get(url).call()?.body_mut().read_json()?;
-        let achievement_ids: Vec<_> = achievements.into_iter().map(|a| a.id).collect();
-
-        Ok(achievement_ids)
+        Ok(names)
     }
 
     fn devices(self) -> anyhow::Result<Vec<pcap::Device>> {
@@ -193,12 +154,27 @@ impl Game {
             .collect())
     }
 
+    // Forwards the packets of every connected device into one channel.
+    fn capture_devices(
+        self,
+        progress: impl Fn(Progress) + Clone + Send + 'static,
+    ) -> anyhow::Result<mpsc::Receiver<Vec<u8>>> {
+        let (device_tx, device_rx) = mpsc::channel();
+        for (i, device) in self.devices()?.into_iter().enumerate() {
+            let device_tx = device_tx.clone();
+            let progress = progress.clone();
+            std::thread::spawn(move || self.capture_device(i, device, &device_tx, progress));
+        }
+
+        Ok(device_rx)
+    }
+
     fn capture_device(
         self,
         i: usize,
         device: pcap::Device,
         device_tx: &mpsc::Sender<Vec<u8>>,
-        message_tx: &mpsc::Sender<Message>,
+        progress: impl Fn(Progress),
     ) -> anyhow::Result<()> {
         let packet_filer = match self {
             Game::Hsr => "udp portrange 23301-23302",
@@ -217,17 +193,7 @@ impl Game {
 
             capture.filter(packet_filer, true)?;
 
-            message_tx
-                .send(Message::Toast({
-                    let mut toast = egui_notify::Toast::success(format!("Device {i} Ready~!"));
-                    toast.duration(None);
-                    toast
-                }))
-                .unwrap();
-
-            message_tx
-                .send(Message::GoTo(State::Waiting("Running".to_string())))
-                .unwrap();
+            progress(Progress::DeviceReady(i));
             tracing::info!("Device {i} Ready~!");
 
             let mut has_captured = false;
@@ -244,15 +210,7 @@ impl Game {
                 }
             }
 
-            message_tx
-                .send(Message::Toast({
-                    let mut toast = egui_notify::Toast::error(format!(
-                        "Device {i} Error. Starting up again..."
-                    ));
-                    toast.duration(None);
-                    toast
-                }))
-                .unwrap();
+            progress(Progress::DeviceRestarting(i));
             tracing::info!("Device {i} Error. Starting up again...");
         }
     }
@@ -271,7 +229,10 @@ pub fn pulls_from_game_path(path: &Path) -> anyhow::Result<String> {
         .collect();
     paths.sort();
 
-    let mut cache_path = paths[paths.len() - 1].clone();
+    let mut cache_path = paths
+        .last()
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("No web cache found in {}", path.display()))?;
     cache_path.push("Cache");
     cache_path.push("Cache_Data");
     cache_path.push("data_2");
@@ -298,192 +259,6 @@ pub fn pulls_from_game_path(path: &Path) -> anyhow::Result<String> {
     Err(anyhow::anyhow!("Couldn't find pull url"))
 }
 
-#[derive(serde::Deserialize)]
-#[allow(non_snake_case)]
-struct ReliquaryExcelConfigDataEntry {
-    equipType: String,
-    id: u32,
-    rankLevel: u32,
-    setId: u32,
-}
-
-#[derive(serde::Deserialize)]
-#[allow(non_snake_case)]
-struct DisplayItemExcelConfigDataEntry {
-    displayType: String,
-    nameTextMapHash: u32,
-    param: u32,
-}
-
-#[derive(Debug)]
-#[allow(non_snake_case)]
-pub struct ArtifactData {
-    pub setKey: String,
-    pub slotKey: String,
-    pub rarity: u32,
-}
-
-fn map_equip_type_to_good(input: &str) -> String {
-    match input {
-        "EQUIP_BRACER" => "flower",
-        "EQUIP_NECKLACE" => "plume",
-        "EQUIP_SHOES" => "sands",
-        "EQUIP_RING" => "goblet",
-        "EQUIP_DRESS" => "circlet",
-        _ => input,
-    }.to_owned()
-}
-
-fn map_set_name_to_good(input: &str) -> String {
-    let mut result = String::new();
-    let mut capitalize_next = true;
-
-    for c in input.chars() {
-        if c.is_alphabetic() {
-            if capitalize_next {
-                result.extend(c.to_uppercase());
-                capitalize_next = false;
-            } else {
-                result.extend(c.to_lowercase());
-            }
-        } else if c != '\'' {
-            capitalize_next = true;
-        }
-    }
-
-    result
-}
-
-pub fn build_artifact_id_map() -> anyhow::Result<HashMap<u32, ArtifactData>> {
-    let reliquary_excel_config_data: Vec<ReliquaryExcelConfigDataEntry> =
-        serde_json::from_str(include_str!("../../data/ReliquaryExcelConfigData.json"))?;
-
-    let display_item_excel_config_data: Vec<DisplayItemExcelConfigDataEntry> =
-        serde_json::from_str(include_str!("../../data/DisplayItemExcelConfigData.json"))?;
-
-    let text_map_en: HashMap<String, String> =
-        serde_json::from_str(include_str!("../../data/TextMapEN.json"))?;
-
-    // Map setId -> nameTextMapHash
-    let mut setid_to_hash = HashMap::new();
-    for entry in display_item_excel_config_data {
-        if entry.displayType == "RELIQUARY_ITEM" {
-            setid_to_hash.insert(entry.param, entry.nameTextMapHash);
-        }
-    }
-
-    // Final id -> ArtifactData map
-    let mut result = HashMap::new();
-    for entry in reliquary_excel_config_data {
-        if let Some(hash_num) = setid_to_hash.get(&entry.setId) {
-            let hash_str = hash_num.to_string();
-            if let Some(text) = text_map_en.get(&hash_str) {
-                result.insert(
-                    entry.id,
-                    ArtifactData {
-                        setKey: map_set_name_to_good(text),
-                        slotKey: map_equip_type_to_good(&entry.equipType).to_string(),
-                        rarity: entry.rankLevel,
-                    },
-                );
-            }
-        }
-    }
-
-    for (id, data) in &result {
-        tracing::trace!("ID {} => {:?}", id, data);
-    }
-
-    Ok(result)
-}
-
-#[derive(serde::Deserialize)]
-#[allow(non_snake_case)]
-struct ReliquaryMainPropExcelConfigDataEntry {
-    id: u32,
-    propType: String,
-}
-
-fn map_main_prop_to_good(input: &str) -> String {
-    match input {
-        "FIGHT_PROP_HP" => "hp",
-        "FIGHT_PROP_HP_PERCENT" => "hp_",
-        "FIGHT_PROP_ATTACK" => "atk",
-        "FIGHT_PROP_ATTACK_PERCENT" => "atk_",
-        "FIGHT_PROP_DEFENSE" => "def",
-        "FIGHT_PROP_DEFENSE_PERCENT" => "def_",
-        "FIGHT_PROP_ELEMENT_MASTERY" => "eleMas",
-        "FIGHT_PROP_CHARGE_EFFICIENCY" => "enerRech_",
-        "FIGHT_PROP_HEAL_ADD" => "heal_",
-        "FIGHT_PROP_CRITICAL" => "critRate_",
-        "FIGHT_PROP_CRITICAL_HURT" => "critDMG_",
-        "FIGHT_PROP_PHYSICAL_ADD_HURT" => "physical_dmg_",
-        "FIGHT_PROP_WIND_ADD_HURT" => "anemo_dmg_",
-        "FIGHT_PROP_ROCK_ADD_HURT" => "geo_dmg_",
-        "FIGHT_PROP_ELEC_ADD_HURT" => "electro_dmg_",
-        "FIGHT_PROP_WATER_ADD_HURT" => "hydro_dmg_",
-        "FIGHT_PROP_FIRE_ADD_HURT" => "pyro_dmg_",
-        "FIGHT_PROP_ICE_ADD_HURT" => "cryo_dmg_",
-        "FIGHT_PROP_GRASS_ADD_HURT" => "dendro_dmg_",
-        _ => input,
-    }.to_owned()
-}
-
-pub fn build_main_prop_map() -> anyhow::Result<HashMap<u32, String>> {
-    let reliquary_main_prop_excel_config: Vec<ReliquaryMainPropExcelConfigDataEntry> =
-        serde_json::from_str(include_str!("../../data/ReliquaryMainPropExcelConfigData.json"))?;
-
-    let mut result = HashMap::new();
-    for entry in reliquary_main_prop_excel_config {
-        result.insert(
-            entry.id,
-            map_main_prop_to_good(&entry.propType),
-        );
-    }
-
-    for (id, data) in &result {
-        tracing::trace!("ID {} => {:?}", id, data);
-    }
-
-    Ok(result)
-}
-
-#[derive(serde::Deserialize)]
-#[allow(non_snake_case)]
-struct ReliquaryAffixExcelConfigDataEntry {
-    id: u32,
-    propType: String,
-    propValue: f64
-}
-
-#[derive(Debug, Clone, serde::Serialize)]
-#[allow(non_snake_case)]
-pub struct Substat {
-    pub key: String,
-    pub value: f64,
-}
-
-pub fn build_affix_prop_map() -> anyhow::Result<HashMap<u32, Substat>> {
-    let reliquary_affix_excel_config: Vec<ReliquaryAffixExcelConfigDataEntry> =
-        serde_json::from_str(include_str!("../../data/ReliquaryAffixExcelConfigData.json"))?;
-
-    let mut result = HashMap::new();
-    for entry in reliquary_affix_excel_config {
-        let key = map_main_prop_to_good(&entry.propType);
-        result.insert(
-            entry.id,
-            Substat{
-                value: if key.ends_with("_") { entry.propValue * 100. } else { entry.propValue },
-                key: key,
-            }
-        );
-    }
-
-    for (id, data) in &result {
-        tracing::trace!("ID {} => {:?}", id, data);
-    }
-
-    Ok(result)
-}
-
-pub use gi::Artifact;
\ No newline at end of file
+pub use good::{Artifact, Good, SetKey, SlotKey, StatKey, Substat, max_level};
+pub use session::{Achievements, Artifacts, Event, Session};
+pub use substats::Affix;
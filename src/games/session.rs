@@ -0,0 +1,106 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, mpsc},
+    thread,
+};
+
+use super::{Artifact, Game, Progress};
+use crate::data::{Language, Tables};
+
+pub struct Achievements {
+    pub completed: Vec<u32>,
+    // Titles in the requested language, for display only.
+    pub names: BTreeMap<u32, String>,
+}
+
+pub struct Artifacts {
+    pub artifacts: Vec<Artifact>,
+    // One line per artifact that couldn't be converted to GOOD.
+    pub warnings: Vec<String>,
+}
+
+pub enum Event<T> {
+    Progress(Progress),
+    // Always the last event of a session.
+    Done(anyhow::Result<T>),
+}
+
+// A capture running in the background. Iterate it for progress, or `wait`
+// for the result.
+pub struct Session<T> {
+    event_rx: mpsc::Receiver<Event<T>>,
+    done: bool,
+}
+
+impl Session<Achievements> {
+    pub fn achievements(game: Game, language: Language) -> Self {
+        let (event_tx, event_rx) = mpsc::channel();
+        let progress = progress(&event_tx);
+
+        thread::spawn(move || {
+            let result = game.capture_achievements(language, progress);
+            let _ = event_tx.send(Event::Done(result));
+        });
+
+        Self {
+            event_rx,
+            done: false,
+        }
+    }
+}
+
+impl Session<Artifacts> {
+    pub fn artifacts(game: Game, tables: Arc<Tables>) -> Self {
+        let (event_tx, event_rx) = mpsc::channel();
+        let progress = progress(&event_tx);
+
+        thread::spawn(move || {
+            let result = game.capture_artifacts(&tables, progress);
+            let _ = event_tx.send(Event::Done(result));
+        });
+
+        Self {
+            event_rx,
+            done: false,
+        }
+    }
+}
+
+impl<T> Session<T> {
+    // Blocks until the game sent the data, passing progress on.
+    pub fn wait(self, mut progress: impl FnMut(Progress)) -> anyhow::Result<T> {
+        for event in self {
+            match event {
+                Event::Progress(p) => progress(p),
+                Event::Done(result) => return result,
+            }
+        }
+
+        Err(anyhow::anyhow!("Capture stopped"))
+    }
+}
+
+impl<T> Iterator for Session<T> {
+    type Item = Event<T>;
+
+    // The device threads outlive the capture, so the channel never closes by
+    // itself and iteration ends with `Done` instead.
+    fn next(&mut self) -> Option<Event<T>> {
+        if self.done {
+            return None;
+        }
+
+        let event = self.event_rx.recv().ok()?;
+        self.done = matches!(event, Event::Done(_));
+        Some(event)
+    }
+}
+
+fn progress<T: Send + 'static>(
+    event_tx: &mpsc::Sender<Event<T>>,
+) -> impl Fn(Progress) + Clone + Send + 'static {
+    let event_tx = event_tx.clone();
+    move |progress| {
+        let _ = event_tx.send(Event::Progress(progress));
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+
+use super::{StatKey, Substat};
+
+// Every id of `appendPropIdList` is a single roll of an affix tier from
+// `ReliquaryAffixExcelConfigData`. The game keeps those tiers as `f32`, sums
+// them in roll order and only rounds the total when displaying it: percent
+// stats to one decimal, flat stats to an integer, halves away from zero.
+// Summing the tiers as `f64` and rounding twice lands on the other side of a
+// `.x5` boundary whenever the `f32` total sits just below it.
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Affix {
+    pub key: StatKey,
+    pub value: f32,
+}
+
+pub fn display_value(key: StatKey, total: f32) -> f64 {
+    let total = f64::from(total);
+
+    if key.is_percent() {
+        (total * 1000.0).round() / 10.0
+    } else {
+        total.round()
+    }
+}
+
+// Fails with the first affix id missing from the table.
+pub fn reconstruct(affix_ids: &[u32], affixes: &BTreeMap<u32, Affix>) -> Result<Vec<Substat>, u32> {
+    let mut totals: Vec<(StatKey, f32)> = Vec::new();
+
+    for affix_id in affix_ids {
+        let affix = affixes.get(affix_id).ok_or(*affix_id)?;
+
+        match totals.iter_mut().find(|(key, _)| *key == affix.key) {
+            Some((_, total)) => *total += affix.value,
+            None => totals.push((affix.key, affix.value)),
+        }
+    }
+
+    Ok(totals
+        .into_iter()
+        .map(|(key, total)| Substat {
+            key,
+            value: display_value(key, total),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every substat affix of the 1 to 5 star depots, first id and tier values.
+    // Ids are `depot, prop, tier`, like in `ReliquaryAffixExcelConfigData`.
+    #[rustfmt::skip]
+    const TIERS: &[(u32, StatKey, &[&str])] = &[
+        (101021, StatKey::Hp, &["23.9", "29.88"]),
+        (101031, StatKey::HpPercent, &["0.0117", "0.0146"]),
+        (101051, StatKey::Atk, &["1.56", "1.95"]),
+        (101061, StatKey::AtkPercent, &["0.0117", "0.0146"]),
+        (101081, StatKey::Def, &["1.85", "2.31"]),
+        (101091, StatKey::DefPercent, &["0.0146", "0.0182"]),
+        (101201, StatKey::CritRate, &["0.0078", "0.0097"]),
+        (101221, StatKey::CritDmg, &["0.0155", "0.0194"]),
+        (101231, StatKey::EnerRech, &["0.013", "0.0162"]),
+        (101241, StatKey::EleMas, &["4.66", "5.83"]),
+        (201021, StatKey::Hp, &["50.19", "60.95", "71.7"]),
+        (201031, StatKey::HpPercent, &["0.0163", "0.0198", "0.0233"]),
+        (201051, StatKey::Atk, &["3.27", "3.97", "4.67"]),
+        (201061, StatKey::AtkPercent, &["0.0163", "0.0198", "0.0233"]),
+        (201081, StatKey::Def, &["3.89", "4.72", "5.56"]),
+        (201091, StatKey::DefPercent, &["0.0204", "0.0248", "0.0291"]),
+        (201201, StatKey::CritRate, &["0.0109", "0.0132", "0.0155"]),
+        (201221, StatKey::CritDmg, &["0.0218", "0.0264", "0.0311"]),
+        (201231, StatKey::EnerRech, &["0.0181", "0.022", "0.0259"]),
+        (201241, StatKey::EleMas, &["6.53", "7.93", "9.33"]),
+        (301021, StatKey::Hp, &["100.38", "114.72", "129.06", "143.4"]),
+        (301031, StatKey::HpPercent, &["0.0245", "0.028", "0.0315", "0.035"]),
+        (301051, StatKey::Atk, &["6.54", "7.47", "8.4", "9.34"]),
+        (301061, StatKey::AtkPercent, &["0.0245", "0.028", "0.0315", "0.035"]),
+        (301081, StatKey::Def, &["7.78", "8.89", "10", "11.11"]),
+        (301091, StatKey::DefPercent, &["0.0306", "0.035", "0.0393", "0.0437"]),
+        (301201, StatKey::CritRate, &["0.0163", "0.0186", "0.021", "0.0233"]),
+        (301221, StatKey::CritDmg, &["0.0326", "0.0373", "0.042", "0.0466"]),
+        (301231, StatKey::EnerRech, &["0.0272", "0.0311", "0.035", "0.0389"]),
+        (301241, StatKey::EleMas, &["9.79", "11.19", "12.59", "13.99"]),
+        (401021, StatKey::Hp, &["167.3", "191.2", "215.1", "239"]),
+        (401031, StatKey::HpPercent, &["0.0326", "0.0373", "0.042", "0.0466"]),
+        (401051, StatKey::Atk, &["10.89", "12.45", "14", "15.56"]),
+        (401061, StatKey::AtkPercent, &["0.0326", "0.0373", "0.042", "0.0466"]),
+        (401081, StatKey::Def, &["12.96", "14.82", "16.67", "18.52"]),
+        (401091, StatKey::DefPercent, &["0.0408", "0.0466", "0.0525", "0.0583"]),
+        (401201, StatKey::CritRate, &["0.0218", "0.0249", "0.028", "0.0311"]),
+        (401221, StatKey::CritDmg, &["0.0435", "0.0497", "0.0559", "0.0622"]),
+        (401231, StatKey::EnerRech, &["0.0363", "0.0414", "0.0466", "0.0518"]),
+        (401241, StatKey::EleMas, &["13.06", "14.92", "16.79", "18.65"]),
+        (501021, StatKey::Hp, &["209.13", "239", "268.88", "298.75"]),
+        (501031, StatKey::HpPercent, &["0.0408", "0.0466", "0.0525", "0.0583"]),
+        (501051, StatKey::Atk, &["13.62", "15.56", "17.51", "19.45"]),
+        (501061, StatKey::AtkPercent, &["0.0408", "0.0466", "0.0525", "0.0583"]),
+        (501081, StatKey::Def, &["16.2", "18.52", "20.83", "23.15"]),
+        (501091, StatKey::DefPercent, &["0.051", "0.0583", "0.0656", "0.0729"]),
+        (501201, StatKey::CritRate, &["0.0272", "0.0311", "0.035", "0.0389"]),
+        (501221, StatKey::CritDmg, &["0.0544", "0.0622", "0.0699", "0.0777"]),
+        (501231, StatKey::EnerRech, &["0.0453", "0.0518", "0.0583", "0.0648"]),
+        (501241, StatKey::EleMas, &["16.32", "18.65", "20.98", "23.31"]),
+    ];
+
+    // Initial roll plus five upgrades.
+    const MAX_ROLLS: usize = 6;
+
+    fn affixes() -> BTreeMap<u32, Affix> {
+        let mut affixes = BTreeMap::new();
+        for (first_id, key, values) in TIERS {
+            for (tier, value) in values.iter().enumerate() {
+                affixes.insert(
+                    first_id + tier as u32,
+                    Affix {
+                        key: *key,
+                        value: value.parse().unwrap(),
+                    },
+                );
+            }
+        }
+        affixes
+    }
+
+    // All multisets of `len` rolls among `tiers`, as non-decreasing tier indices.
+    fn roll_combinations(len: usize, tiers: usize) -> Vec<Vec<usize>> {
+        let mut combinations = vec![Vec::new()];
+        for _ in 0..len {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|c: Vec<usize>| {
+                    let start = c.last().copied().unwrap_or(0);
+                    (start..tiers).map(move |tier| {
+                        let mut c = c.clone();
+                        c.push(tier);
+                        c
+                    })
+                })
+                .collect();
+        }
+        combinations
+    }
+
+    // Exact display of an `f32` total, without going through `f64` arithmetic:
+    // `total * scale` as a fraction of powers of two, rounded half away from zero.
+    fn exact_display(total: f32, scale: i128) -> i128 {
+        let bits = total.to_bits();
+        let exponent = ((bits >> 23) & 0xff) as i32;
+        let mantissa = (bits & 0x7f_ffff) as i128;
+        let (mantissa, exponent) = match exponent {
+            0 => (mantissa, -149),
+            exponent => (mantissa | 0x80_0000, exponent - 150),
+        };
+
+        let numerator = mantissa * scale;
+        if exponent >= 0 {
+            return numerator << exponent;
+        }
+        let denominator = 1i128 << -exponent;
+        let (down, remainder) = (numerator / denominator, numerator % denominator);
+        if remainder * 2 >= denominator {
+            down + 1
+        } else {
+            down
+        }
+    }
+
+    #[test]
+    fn every_roll_combination_rounds_like_the_game() {
+        let affixes = affixes();
+
+        // Tiers of the same affix only differ in the last digit of their id.
+        let mut depots: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for id in affixes.keys() {
+            depots.entry(id / 10).or_default().push(*id);
+        }
+        assert_eq!(depots.len(), 50);
+
+        for tiers in depots.values() {
+            let key = affixes[&tiers[0]].key;
+            let (scale, shown) = if key.is_percent() {
+                (1000, 10.0)
+            } else {
+                (1, 1.0)
+            };
+
+            for len in 1..=MAX_ROLLS {
+                for combination in roll_combinations(len, tiers.len()) {
+                    let ids: Vec<u32> = combination.iter().map(|t| tiers[*t]).collect();
+
+                    let mut total = 0f32;
+                    for id in &ids {
+                        let affix = &affixes[id];
+                        assert_eq!(affix.key, key, "{ids:?}");
+                        total += affix.value;
+                    }
+                    let expected = exact_display(total, scale) as f64 / shown;
+
+                    let substats = reconstruct(&ids, &affixes).unwrap();
+                    assert_eq!(substats.len(), 1, "{ids:?}");
+                    assert_eq!(substats[0].key, key, "{ids:?}");
+                    assert_eq!(substats[0].value, expected, "{ids:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ties_round_on_the_f32_total() {
+        // 0.0435 is stored as 0.04349999 and 0.0285 as 0.02850000001, so the
+        // same decimal tie shows on both sides.
+        assert_eq!(display_value(StatKey::CritDmg, 0.0435), 4.3);
+        assert_eq!(display_value(StatKey::CritDmg, 0.0285), 2.9);
+        assert_eq!(exact_display(0.0435, 1000), 43);
+        assert_eq!(exact_display(0.0285, 1000), 29);
+    }
+
+    #[test]
+    fn known_substats() {
+        let affixes = affixes();
+
+        let cases: &[(&[u32], StatKey, f64)] = &[
+            (&[501204], StatKey::CritRate, 3.9),
+            (&[501201, 501201, 501201], StatKey::CritRate, 8.2),
+            (&[501201, 501202, 501202, 501202], StatKey::CritRate, 12.0),
+            (
+                &[501224, 501224, 501224, 501224, 501224, 501224],
+                StatKey::CritDmg,
+                46.6,
+            ),
+            (&[501021, 501023], StatKey::Hp, 478.0),
+            (&[501244, 501244], StatKey::EleMas, 47.0),
+            (&[401223, 401221], StatKey::CritDmg, 9.9),
+        ];
+
+        for (ids, key, value) in cases {
+            let substats = reconstruct(ids, &affixes).unwrap();
+            assert_eq!(substats.len(), 1);
+            assert_eq!(substats[0].key, *key);
+            assert_eq!(substats[0].value, *value, "{ids:?}");
+        }
+    }
+
+    #[test]
+    fn groups_rolls_by_stat_in_first_seen_order() {
+        let substats = reconstruct(&[501244, 501201, 501241], &affixes()).unwrap();
+
+        let keys: Vec<_> = substats.iter().map(|s| s.key).collect();
+        assert_eq!(keys, [StatKey::EleMas, StatKey::CritRate]);
+        assert_eq!(substats[0].value, 40.0);
+        assert_eq!(substats[1].value, 2.7);
+    }
+
+    #[test]
+    fn reports_unknown_affix_ids() {
+        assert_eq!(
+            reconstruct(&[501201, 999999], &affixes()).unwrap_err(),
+            999999
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use super::{Artifact, SlotKey, StatKey, max_level};
+
+fn legal_main_stat(slot: SlotKey, key: StatKey) -> bool {
+    match slot {
+        SlotKey::Flower => key == StatKey::Hp,
+        SlotKey::Plume => key == StatKey::Atk,
+        SlotKey::Sands => matches!(
+            key,
+            StatKey::HpPercent
+                | StatKey::AtkPercent
+                | StatKey::DefPercent
+                | StatKey::EleMas
+                | StatKey::EnerRech
+        ),
+        SlotKey::Goblet => {
+            matches!(
+                key,
+                StatKey::HpPercent | StatKey::AtkPercent | StatKey::DefPercent | StatKey::EleMas
+            ) || key.as_str().ends_with("_dmg_")
+        }
+        SlotKey::Circlet => matches!(
+            key,
+            StatKey::HpPercent
+                | StatKey::AtkPercent
+                | StatKey::DefPercent
+                | StatKey::EleMas
+                | StatKey::CritRate
+                | StatKey::CritDmg
+                | StatKey::Heal
+        ),
+    }
+}
+
+fn legal_substat(key: StatKey) -> bool {
+    !matches!(key, StatKey::Heal) && !key.as_str().ends_with("_dmg_")
+}
+
+// Artifacts drop with `rarity - 2` or `rarity - 1` lines and every fourth
+// level adds one until there are four.
+fn substat_counts(rarity: u32, level: u32) -> [usize; 2] {
+    let initial = rarity.saturating_sub(2);
+    [
+        (initial + level / 4).min(4) as usize,
+        (initial + 1 + level / 4).min(4) as usize,
+    ]
+}
+
+// Problems that would make optimizers reject or misread the artifact.
+pub fn validate(artifact: &Artifact) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if !artifact.setKey.is_known() {
+        problems.push(format!("unknown set {}", artifact.setKey));
+    }
+
+    match max_level(artifact.rarity) {
+        Some(max) if artifact.level > max => problems.push(format!(
+            "level {} is above {max} for {} stars",
+            artifact.level, artifact.rarity
+        )),
+        Some(_) => {
+            let counts = substat_counts(artifact.rarity, artifact.level);
+            if !counts.contains(&artifact.substats.len()) {
+                problems.push(format!(
+                    "{} substats at level {}, expected {}",
+                    artifact.substats.len(),
+                    artifact.level,
+                    if counts[0] == counts[1] {
+                        counts[0].to_string()
+                    } else {
+                        format!("{} or {}", counts[0], counts[1])
+                    }
+                ));
+            }
+        }
+        None => problems.push(format!("invalid rarity {}", artifact.rarity)),
+    }
+
+    if !legal_main_stat(artifact.slotKey, artifact.mainStatKey) {
+        problems.push(format!(
+            "{} can't be the main stat of a {}",
+            artifact.mainStatKey, artifact.slotKey
+        ));
+    }
+
+    for (i, substat) in artifact.substats.iter().enumerate() {
+        if !legal_substat(substat.key) {
+            problems.push(format!("{} can't be a substat", substat.key));
+        } else if substat.key == artifact.mainStatKey {
+            problems.push(format!("substat {} duplicates the main stat", substat.key));
+        } else if artifact.substats[..i].iter().any(|s| s.key == substat.key) {
+            problems.push(format!("substat {} appears twice", substat.key));
+        }
+
+        if substat.value.is_nan() || substat.value <= 0.0 {
+            problems.push(format!(
+                "substat {} has value {}",
+                substat.key, substat.value
+            ));
+        }
+    }
+
+    problems
+}
+
+// Indices of the artifacts with problems, and their problems.
+pub fn validate_all(artifacts: &[Artifact]) -> Vec<(usize, Vec<String>)> {
+    artifacts
+        .iter()
+        .enumerate()
+        .map(|(i, a)| (i, validate(a)))
+        .filter(|(_, problems)| !problems.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::{SetKey, Substat};
+
+    fn artifact(
+        slot: SlotKey,
+        main: StatKey,
+        rarity: u32,
+        level: u32,
+        substats: &[StatKey],
+    ) -> Artifact {
+        Artifact {
+            setKey: SetKey::GladiatorsFinale,
+            slotKey: slot,
+            level,
+            rarity,
+            mainStatKey: main,
+            lock: false,
+            substats: substats
+                .iter()
+                .map(|key| Substat {
+                    key: *key,
+                    value: 5.0,
+                })
+                .collect(),
+        }
+    }
+
+    const FOUR: [StatKey; 4] = [
+        StatKey::CritRate,
+        StatKey::CritDmg,
+        StatKey::Atk,
+        StatKey::Def,
+    ];
+
+    #[test]
+    fn accepts_valid_artifacts() {
+        assert!(validate(&artifact(SlotKey::Flower, StatKey::Hp, 5, 20, &FOUR)).is_empty());
+        assert!(
+            validate(&artifact(
+                SlotKey::Goblet,
+                StatKey::PyroDmg,
+                5,
+                0,
+                &FOUR[..3]
+            ))
+            .is_empty()
+        );
+        assert!(validate(&artifact(SlotKey::Circlet, StatKey::Heal, 4, 16, &FOUR)).is_empty());
+        assert!(
+            validate(&artifact(
+                SlotKey::Sands,
+                StatKey::EnerRech,
+                4,
+                3,
+                &FOUR[..2]
+            ))
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn checks_level_bounds_by_rarity() {
+        assert_eq!(
+            validate(&artifact(SlotKey::Flower, StatKey::Hp, 5, 21, &FOUR)).len(),
+            1
+        );
+        assert_eq!(
+            validate(&artifact(SlotKey::Flower, StatKey::Hp, 4, 17, &FOUR)).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn checks_substat_count_against_level() {
+        let problems = validate(&artifact(SlotKey::Flower, StatKey::Hp, 5, 4, &FOUR[..3]));
+        assert_eq!(problems, ["3 substats at level 4, expected 4"]);
+
+        let problems = validate(&artifact(SlotKey::Flower, StatKey::Hp, 5, 0, &FOUR[..2]));
+        assert_eq!(problems, ["2 substats at level 0, expected 3 or 4"]);
+    }
+
+    #[test]
+    fn checks_main_stats_and_substats() {
+        let problems = validate(&artifact(SlotKey::Plume, StatKey::CritRate, 5, 20, &FOUR));
+        assert_eq!(
+            problems,
+            [
+                "critRate_ can't be the main stat of a plume",
+                "substat critRate_ duplicates the main stat"
+            ]
+        );
+
+        let substats = [
+            StatKey::CritRate,
+            StatKey::CritRate,
+            StatKey::PyroDmg,
+            StatKey::Def,
+        ];
+        let problems = validate(&artifact(SlotKey::Flower, StatKey::Hp, 5, 20, &substats));
+        assert_eq!(
+            problems,
+            [
+                "substat critRate_ appears twice",
+                "pyro_dmg_ can't be a substat"
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_unknown_sets() {
+        let mut artifact = artifact(SlotKey::Flower, StatKey::Hp, 5, 20, &FOUR);
+        artifact.setKey = SetKey::from_good("SomeFutureSet").unwrap();
+        assert_eq!(validate(&artifact), ["unknown set SomeFutureSet"]);
+    }
+}
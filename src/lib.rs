@@ -0,0 +1,17 @@
+//! Capturing, exporting and syncing of game data for stardb, without any UI.
+//! The `stardb-exporter` app and command line are built on top of it.
+//!
+//! ```no_run
+//! use stardb_exporter::{data::Language, games::{Game, Session}};
+//!
+//! let achievements = Session::achievements(Game::Hsr, Language::En).wait(|_| {})?;
+//! println!("{} completed", achievements.completed.len());
+//!
+//! let url = Game::Zzz.locate_gacha_url(None)?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+pub mod data;
+pub mod export;
+pub mod games;
+pub mod stardb;
@@ -1,15 +1,49 @@
 #![windows_subsystem = "windows"]
 
 mod app;
-mod games;
+mod cli;
+mod events;
+mod server;
+mod snapshots;
+mod sync_queue;
+mod tasks;
 mod themes;
 mod ui;
+mod vault;
+mod webhook;
+
+use std::path::PathBuf;
+
+use stardb_exporter::{data, export, games, stardb};
 
 const APP_ID: &str = "Stardb Exporter";
 
 fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("data-prep") {
+        return data::prep::run(&args[1..]);
+    }
+
+    if let Some(command) = args.first()
+        && cli::is_command(command)
+    {
+        let _guard = tracing_init()?;
+        return cli::run(&args);
+    }
+
     let _guard = tracing_init()?;
 
+    // The app can mirror its progress and results for scripts, see `events`.
+    let events = match args.as_slice() {
+        [flag, target] if flag == "--events" => {
+            if target == "-" {
+                cli::attach_console();
+            }
+            Some(events::EventLog::open(target)?)
+        }
+        _ => None,
+    };
+
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_min_inner_size([400.0, 300.0])
@@ -23,7 +57,7 @@ fn main() -> anyhow::Result<()> {
     eframe::run_native(
         APP_ID,
         native_options,
-        Box::new(|cc| Ok(Box::new(app::App::new(cc)))),
+        Box::new(|cc| Ok(Box::new(app::App::new(cc, events)))),
     )
     .map_err(|e| anyhow::anyhow!("{e}"))?;
 
@@ -31,8 +65,7 @@ fn main() -> anyhow::Result<()> {
 }
 
 fn tracing_init() -> anyhow::Result<tracing_appender::non_blocking::WorkerGuard> {
-    let mut storage_dir =
-        anyhow::Context::context(eframe::storage_dir(APP_ID), "Storage dir not found")?;
+    let mut storage_dir = storage_dir()?;
     storage_dir.push("log");
 
     let appender = tracing_appender::rolling::daily(storage_dir, "log");
@@ -46,3 +79,31 @@ fn tracing_init() -> anyhow::Result<tracing_appender::non_blocking::WorkerGuard>
 
     Ok(guard)
 }
+
+// Where settings, logs, data tables and snapshots are kept. The same dir
+// eframe persists the app settings in, found without depending on it.
+fn storage_dir() -> anyhow::Result<PathBuf> {
+    let dir = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(|p| PathBuf::from(p).join(APP_ID).join("data"))
+    } else if cfg!(target_os = "macos") {
+        home::home_dir().map(|p| {
+            p.join("Library")
+                .join("Application Support")
+                .join(APP_ID.replace(|c: char| c.is_ascii_whitespace(), "-"))
+        })
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .or_else(|| home::home_dir().map(|p| p.join(".local").join("share")))
+            .map(|p| {
+                p.join(
+                    APP_ID
+                        .to_lowercase()
+                        .replace(|c: char| c.is_ascii_whitespace(), ""),
+                )
+            })
+    };
+
+    anyhow::Context::context(dir, "Storage dir not found")
+}
@@ -0,0 +1,384 @@
+// Opt-in server on localhost, so that companion tools can pick up the latest
+// capture without going through the clipboard.
+//
+//   GET /gi/good, /gi/achievements, /hsr/achievements
+//       the cached last capture, in the same format as the exports
+//   GET /pulls/url
+//       the last pull url found, as {"game": ..., "url": ...}
+//   WS  /events
+//       {"event": "capture", "game": ..., "kind": ...} for every new result
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use tungstenite::WebSocket;
+
+use crate::{
+    export,
+    games::Game,
+    snapshots::{self, last::Capture},
+};
+
+pub const DEFAULT_PORT: u16 = 47_817;
+
+// How long a client may take to send its request head, or to take an event.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy)]
+pub enum Kind {
+    Achievements,
+    Artifacts,
+    Pulls,
+}
+
+impl Kind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Kind::Achievements => "achievements",
+            Kind::Artifacts => "artifacts",
+            Kind::Pulls => "pulls",
+        }
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    pull_url: Option<(Game, String)>,
+    // Each websocket client has a thread of its own, see `serve_events`.
+    clients: Vec<mpsc::Sender<String>>,
+}
+
+// Stops listening when dropped.
+pub struct Server {
+    port: u16,
+    running: Arc<AtomicBool>,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Server {
+    // Port 0 picks a free port, see `port`.
+    pub fn start(port: u16) -> anyhow::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let port = listener.local_addr()?.port();
+
+        let running = Arc::new(AtomicBool::new(true));
+        let shared = Arc::new(Mutex::new(Shared::default()));
+
+        {
+            let running = running.clone();
+            let shared = shared.clone();
+
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if !running.load(Ordering::Relaxed) {
+                        break;
+                    }
+
+                    let Ok(stream) = stream else {
+                        continue;
+                    };
+
+                    let shared = shared.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = handle(stream, &shared) {
+                            tracing::debug!("Server connection: {e}");
+                        }
+                    });
+                }
+            });
+        }
+
+        tracing::info!("Serving captures on http://127.0.0.1:{port}");
+
+        Ok(Self {
+            port,
+            running,
+            shared,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    // Tells the websocket clients about a new result. Pull urls are kept for
+    // `/pulls/url`, but never pushed since they contain an auth key.
+    pub fn publish(&self, game: Game, kind: Kind, pull_url: Option<&str>) {
+        let mut shared = self.shared.lock().unwrap();
+
+        if let Some(url) = pull_url {
+            shared.pull_url = Some((game, url.to_string()));
+        }
+
+        let event = serde_json::json!({
+            "event": "capture",
+            "game": game.id(),
+            "kind": kind.as_str(),
+        })
+        .to_string();
+
+        shared
+            .clients
+            .retain(|client| client.send(event.clone()).is_ok());
+    }
+
+    // Drops the pull url, e.g. when the user wipes their data.
+    pub fn forget(&self) {
+        self.shared.lock().unwrap().pull_url = None;
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        // Wakes up the accept loop so that it sees the flag.
+        let _ = TcpStream::connect((Ipv4Addr::LOCALHOST, self.port));
+    }
+}
+
+fn handle(mut stream: TcpStream, shared: &Mutex<Shared>) -> anyhow::Result<()> {
+    let head = peek_head(&stream)?;
+
+    let path = head.split_whitespace().nth(1).unwrap_or("/").to_string();
+
+    // Browsers send the page's host, which keeps other sites from reaching the
+    // server through DNS rebinding.
+    if !is_local(&head) {
+        return respond(&mut stream, 403, b"{\"error\":\"forbidden\"}", false);
+    }
+
+    if head.contains("upgrade: websocket") {
+        if path == "/events" {
+            let client = tungstenite::accept(stream)?;
+            let (event_tx, event_rx) = mpsc::channel();
+            shared.lock().unwrap().clients.push(event_tx);
+            serve_events(client, event_rx);
+        }
+        return Ok(());
+    }
+
+    // Skips the rest of the request, nothing here takes a body.
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    match route(&path, shared) {
+        Ok(Some(body)) => respond(&mut stream, 200, &body, path != "/pulls/url"),
+        Ok(None) => respond(&mut stream, 404, b"{\"error\":\"not found\"}", true),
+        Err(e) => {
+            let body = serde_json::json!({ "error": e.to_string() }).to_string();
+            respond(&mut stream, 500, body.as_bytes(), true)
+        }
+    }
+}
+
+// Peeked, so that a websocket handshake can still read the whole request. The
+// head may arrive in pieces, so this waits until it is complete or too long.
+fn peek_head(stream: &TcpStream) -> anyhow::Result<String> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    let deadline = Instant::now() + TIMEOUT;
+
+    let mut buf = [0; 4096];
+    loop {
+        let n = stream.peek(&mut buf)?;
+        if n == 0 {
+            return Err(anyhow::anyhow!("Connection closed before the request"));
+        }
+        if n == buf.len() || buf[..n].windows(4).any(|w| w == b"\r\n\r\n") {
+            return Ok(String::from_utf8_lossy(&buf[..n]).to_ascii_lowercase());
+        }
+        if Instant::now() > deadline {
+            return Err(anyhow::anyhow!("Incomplete request head"));
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+// Passes events on to one client until either side goes away. Runs without
+// the lock, so a stalled client only holds up its own thread. Reading answers
+// pings and close frames.
+fn serve_events(mut client: WebSocket<TcpStream>, event_rx: mpsc::Receiver<String>) {
+    let stream = client.get_ref();
+    if stream
+        .set_read_timeout(Some(Duration::from_millis(100)))
+        .and_then(|()| stream.set_write_timeout(Some(TIMEOUT)))
+        .is_err()
+    {
+        return;
+    }
+
+    loop {
+        loop {
+            match event_rx.try_recv() {
+                Ok(event) => {
+                    if client.send(tungstenite::Message::text(event)).is_err() {
+                        return;
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    let _ = client.close(None);
+                    let _ = client.flush();
+                    return;
+                }
+            }
+        }
+
+        // Pongs and close replies are queued by reading and sent by flushing.
+        match client.read() {
+            Err(e) if !timed_out(&e) => return,
+            _ => {}
+        }
+        match client.flush() {
+            Err(e) if !timed_out(&e) => return,
+            _ => {}
+        }
+    }
+}
+
+fn timed_out(e: &tungstenite::Error) -> bool {
+    matches!(
+        e,
+        tungstenite::Error::Io(e)
+            if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut)
+    )
+}
+
+fn is_local(head: &str) -> bool {
+    head.lines()
+        .find_map(|l| l.strip_prefix("host:"))
+        .map(|host| host.trim().rsplit_once(':').map_or(host.trim(), |(h, _)| h))
+        .is_some_and(|host| matches!(host, "127.0.0.1" | "localhost"))
+}
+
+// `None` for unknown paths and captures that don't exist yet.
+fn route(path: &str, shared: &Mutex<Shared>) -> anyhow::Result<Option<Vec<u8>>> {
+    let (game, kind) = match path.trim_end_matches('/') {
+        "/pulls/url" => {
+            let shared = shared.lock().unwrap();
+            let Some((game, url)) = &shared.pull_url else {
+                return Ok(None);
+            };
+            let body = serde_json::json!({ "game": game.id(), "url": url });
+            return Ok(Some(serde_json::to_vec(&body)?));
+        }
+        "/gi/good" => (Game::Gi, snapshots::Kind::Artifacts),
+        "/gi/achievements" => (Game::Gi, snapshots::Kind::Achievements),
+        "/hsr/achievements" => (Game::Hsr, snapshots::Kind::Achievements),
+        _ => return Ok(None),
+    };
+
+    if !snapshots::last::exists(game, kind)? {
+        return Ok(None);
+    }
+
+    let body = match snapshots::last::load(game, kind)? {
+        Capture::Artifacts { artifacts, .. } => export::good(&artifacts, true)?,
+        Capture::Achievements { achievements, .. } => {
+            export::stardb_achievements(game, &achievements)?
+        }
+    };
+
+    Ok(Some(body))
+}
+
+// Other origins may only read the exports, not the pull url.
+fn respond(stream: &mut TcpStream, status: u16, body: &[u8], cors: bool) -> anyhow::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        403 => "Forbidden",
+        404 => "Not Found",
+        _ => "Internal Server Error",
+    };
+
+    write!(stream, "HTTP/1.1 {status} {reason}\r\n")?;
+    write!(stream, "Content-Type: application/json\r\n")?;
+    write!(stream, "Content-Length: {}\r\n", body.len())?;
+    if cors {
+        write!(stream, "Access-Control-Allow-Origin: *\r\n")?;
+    }
+    write!(stream, "Connection: close\r\n\r\n")?;
+    stream.write_all(body)?;
+    stream.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_accepts_local_hosts() {
+        assert!(is_local("get / http/1.1\r\nhost: 127.0.0.1:47817\r\n\r\n"));
+        assert!(is_local("get / http/1.1\r\nhost: localhost\r\n\r\n"));
+        assert!(!is_local(
+            "get / http/1.1\r\nhost: evil.example:47817\r\n\r\n"
+        ));
+        assert!(!is_local("get / http/1.1\r\n\r\n"));
+    }
+
+    #[test]
+    fn serves_pull_urls_and_pushes_events() {
+        let server = Server::start(0).unwrap();
+        let base = format!("127.0.0.1:{}", server.port());
+
+        let response = ureq::get(format!("http://{base}/pulls/url")).call();
+        assert!(matches!(response, Err(ureq::Error::StatusCode(404))));
+
+        let (mut events, _) = tungstenite::connect(format!("ws://{base}/events")).unwrap();
+        // The handshake is done once connect returns, but the server registers
+        // the client on its own thread.
+        while server.shared.lock().unwrap().clients.is_empty() {
+            thread::yield_now();
+        }
+
+        server.publish(
+            Game::Zzz,
+            Kind::Pulls,
+            Some("https://example.com/getGachaLog"),
+        );
+
+        let event: serde_json::Value =
+            serde_json::from_str(events.read().unwrap().to_text().unwrap()).unwrap();
+        assert_eq!(
+            event,
+            serde_json::json!({ "event": "capture", "game": "zzz", "kind": "pulls" })
+        );
+
+        let url: serde_json::Value = ureq::get(format!("http://{base}/pulls/url"))
+            .call()
+            .unwrap()
+            .body_mut()
+            .read_json()
+            .unwrap();
+        assert_eq!(url["game"], "zzz");
+        assert_eq!(url["url"], "https://example.com/getGachaLog");
+    }
+
+    #[test]
+    fn waits_for_the_whole_request_head() {
+        let server = Server::start(0).unwrap();
+
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, server.port())).unwrap();
+        stream.write_all(b"GET /pulls/url HTTP/1.1\r\n").unwrap();
+        stream.flush().unwrap();
+        thread::sleep(Duration::from_millis(50));
+        stream.write_all(b"Host: 127.0.0.1\r\n\r\n").unwrap();
+
+        let mut status = String::new();
+        BufReader::new(stream).read_line(&mut status).unwrap();
+        assert_eq!(status.trim_end(), "HTTP/1.1 404 Not Found");
+    }
+}
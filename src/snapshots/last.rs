@@ -0,0 +1,77 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use super::Kind;
+use crate::games::{Artifact, Game};
+
+// Everything a result screen needs, so that it can be rebuilt without the game.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Capture {
+    Artifacts {
+        timestamp: u64,
+        artifacts: Vec<Artifact>,
+        warnings: Vec<String>,
+    },
+    Achievements {
+        timestamp: u64,
+        achievements: Vec<u32>,
+        names: BTreeMap<u32, String>,
+    },
+}
+
+impl Capture {
+    fn kind(&self) -> Kind {
+        match self {
+            Capture::Artifacts { .. } => Kind::Artifacts,
+            Capture::Achievements { .. } => Kind::Achievements,
+        }
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            Capture::Artifacts { timestamp, .. } | Capture::Achievements { timestamp, .. } => {
+                *timestamp
+            }
+        }
+    }
+}
+
+fn path(game: Game, kind: Kind) -> anyhow::Result<PathBuf> {
+    let mut path = crate::storage_dir()?;
+    path.push("last_capture");
+    path.push(format!("{}-{}.json", game.id(), kind.as_str()));
+    Ok(path)
+}
+
+pub fn forget() -> anyhow::Result<()> {
+    let mut dir = crate::storage_dir()?;
+    dir.push("last_capture");
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)?;
+    }
+
+    Ok(())
+}
+
+pub fn save(game: Game, capture: &Capture) -> anyhow::Result<()> {
+    let path = path(game, capture.kind())?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_vec(capture)?)?;
+
+    Ok(())
+}
+
+pub fn exists(game: Game, kind: Kind) -> anyhow::Result<bool> {
+    Ok(path(game, kind)?.exists())
+}
+
+pub fn load(game: Game, kind: Kind) -> anyhow::Result<Capture> {
+    let path = path(game, kind)?;
+    if !path.exists() {
+        return Err(anyhow::anyhow!("No {} captured yet", kind.as_str()));
+    }
+
+    Ok(serde_json::from_slice(&std::fs::read(&path)?)?)
+}
@@ -0,0 +1,225 @@
+pub mod last;
+
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    data::Language,
+    games::{Artifact, Game, diff},
+};
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Artifacts,
+    Achievements,
+}
+
+impl Kind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Kind::Artifacts => "artifacts",
+            Kind::Achievements => "achievements",
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", content = "data", rename_all = "lowercase")]
+pub enum Data {
+    Artifacts(Vec<Artifact>),
+    Achievements(Vec<u32>),
+}
+
+impl Data {
+    fn kind(&self) -> Kind {
+        match self {
+            Data::Artifacts(_) => Kind::Artifacts,
+            Data::Achievements(_) => Kind::Achievements,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Snapshot {
+    pub timestamp: u64,
+    pub uid: String,
+    #[serde(flatten)]
+    pub data: Data,
+}
+
+pub struct Report {
+    pub uid: String,
+    pub artifacts: Option<ArtifactsReport>,
+    pub achievements: Option<AchievementsReport>,
+}
+
+pub struct ArtifactsReport {
+    pub previous: u64,
+    pub current: u64,
+    pub diff: diff::ArtifactDiff,
+}
+
+pub struct AchievementsReport {
+    pub previous: u64,
+    pub current: u64,
+    pub completed: Vec<u32>,
+    pub names: BTreeMap<u32, String>,
+}
+
+pub fn now() -> u64 {
+    now_millis() / 1000
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+// Deletes the snapshots of every game and UID, and the cached last captures.
+pub fn forget() -> anyhow::Result<()> {
+    let mut dir = crate::storage_dir()?;
+    dir.push("snapshots");
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)?;
+    }
+
+    last::forget()
+}
+
+fn dir(game: Game, uid: &str) -> anyhow::Result<PathBuf> {
+    let mut dir = crate::storage_dir()?;
+    dir.push("snapshots");
+    dir.push(game.id());
+    dir.push(uid);
+    Ok(dir)
+}
+
+pub fn save(game: Game, uid: &str, data: Data) -> anyhow::Result<()> {
+    let dir = dir(game, uid)?;
+    std::fs::create_dir_all(&dir)?;
+
+    let snapshot = Snapshot {
+        timestamp: now(),
+        uid: uid.to_string(),
+        data,
+    };
+    // Named by millisecond, moved past any capture saved in the same one.
+    let bytes = serde_json::to_vec(&snapshot)?;
+    let mut millis = now_millis();
+    let path = loop {
+        let path = dir.join(format!("{}-{millis}.json", snapshot.data.kind().as_str()));
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(mut file) => {
+                file.write_all(&bytes)?;
+                break path;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => millis += 1,
+            Err(e) => return Err(e.into()),
+        }
+    };
+
+    tracing::info!("Saved snapshot {}", path.display());
+
+    Ok(())
+}
+
+// Oldest first. Older versions named snapshots by second, which still sorts
+// them before the ones named by millisecond.
+fn list(game: Game, uid: &str, kind: Kind) -> anyhow::Result<Vec<PathBuf>> {
+    let dir = dir(game, uid)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let prefix = format!("{}-", kind.as_str());
+    let mut snapshots: Vec<(u64, PathBuf)> = std::fs::read_dir(&dir)?
+        .filter_map(|e| {
+            let path = e.ok()?.path();
+            let timestamp = path
+                .file_stem()?
+                .to_str()?
+                .strip_prefix(&prefix)?
+                .parse()
+                .ok()?;
+            Some((timestamp, path))
+        })
+        .collect();
+    snapshots.sort();
+
+    Ok(snapshots.into_iter().map(|(_, path)| path).collect())
+}
+
+fn load(path: &PathBuf) -> anyhow::Result<Snapshot> {
+    Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+}
+
+// The last two snapshots of a kind, if there are two.
+fn latest_pair(game: Game, uid: &str, kind: Kind) -> anyhow::Result<Option<(Snapshot, Snapshot)>> {
+    let paths = list(game, uid, kind)?;
+    let [.., previous, current] = paths.as_slice() else {
+        return Ok(None);
+    };
+
+    Ok(Some((load(previous)?, load(current)?)))
+}
+
+pub fn report(game: Game, uid: &str, language: Language) -> anyhow::Result<Report> {
+    let artifacts = match latest_pair(game, uid, Kind::Artifacts)? {
+        Some((
+            Snapshot {
+                timestamp: previous,
+                data: Data::Artifacts(before),
+                ..
+            },
+            Snapshot {
+                timestamp: current,
+                data: Data::Artifacts(after),
+                ..
+            },
+        )) => Some(ArtifactsReport {
+            previous,
+            current,
+            diff: diff::artifacts(&before, &after),
+        }),
+        _ => None,
+    };
+
+    let achievements = match latest_pair(game, uid, Kind::Achievements)? {
+        Some((
+            Snapshot {
+                timestamp: previous,
+                data: Data::Achievements(before),
+                ..
+            },
+            Snapshot {
+                timestamp: current,
+                data: Data::Achievements(after),
+                ..
+            },
+        )) => Some(AchievementsReport {
+            previous,
+            current,
+            completed: diff::achievements(&before, &after),
+            // Titles are a nicety, the ids are shown without them.
+            names: game.achievement_names(language).unwrap_or_default(),
+        }),
+        _ => None,
+    };
+
+    Ok(Report {
+        uid: uid.to_string(),
+        artifacts,
+        achievements,
+    })
+}
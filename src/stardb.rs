@@ -0,0 +1,378 @@
+// Typed client for the stardb.gg API. Requests on behalf of a user carry the
+// session cookie that `login` and `renew` return.
+
+use std::{collections::BTreeSet, fmt, time::Duration};
+
+use ureq::{Agent, Body, http::Response};
+
+use crate::games::Game;
+
+pub const BASE_URL: &str = "https://stardb.gg/api";
+
+// For a whole request, so that a stalled connection fails like an offline one.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum Error {
+    // The session cookie is missing, invalid or expired. Log in again.
+    AuthExpired,
+    // stardb rejected the request, with its message.
+    Validation(String),
+    // stardb couldn't be reached.
+    Network(ureq::Error),
+    // Any other error status.
+    Status(u16),
+    // A successful status, but not the expected response.
+    InvalidResponse(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::AuthExpired => write!(f, "stardb session expired. Try relogging"),
+            Error::Validation(message) => write!(f, "stardb rejected the request: {message}"),
+            Error::Network(e) => write!(f, "Couldn't reach stardb: {e}"),
+            Error::Status(status) => write!(f, "stardb returned {status}"),
+            Error::InvalidResponse(message) => write!(f, "Unexpected stardb response: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(serde::Serialize)]
+pub struct Login<'a> {
+    pub username: &'a str,
+    pub password: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    // `id=...`, as sent back in the `Cookie` header.
+    pub cookie: String,
+    pub username: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct Achievement {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncMode {
+    // Only adds the captured achievements missing on the account.
+    AddOnly,
+    // Also removes the ones on the account that weren't captured.
+    Mirror,
+}
+
+// Captured achievements against the ones completed on the account, sorted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SyncDiff {
+    pub to_add: Vec<u32>,
+    pub to_remove: Vec<u32>,
+    pub unchanged: Vec<u32>,
+}
+
+impl SyncDiff {
+    pub fn new(completed: &[u32], captured: &[u32]) -> Self {
+        let completed: BTreeSet<u32> = completed.iter().copied().collect();
+        let captured: BTreeSet<u32> = captured.iter().copied().collect();
+
+        Self {
+            to_add: captured.difference(&completed).copied().collect(),
+            to_remove: completed.difference(&captured).copied().collect(),
+            unchanged: captured.intersection(&completed).copied().collect(),
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct PullsImportRequest<'a> {
+    pub url: &'a str,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PullsImport {
+    pub uid: u64,
+}
+
+#[derive(Clone)]
+pub struct Client {
+    base_url: String,
+    agent: Agent,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new(BASE_URL)
+    }
+}
+
+impl Client {
+    // `base_url` includes the `/api` prefix, e.g. `BASE_URL`.
+    pub fn new(base_url: &str) -> Self {
+        let agent = Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_global(Some(TIMEOUT))
+            .build()
+            .into();
+
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            agent,
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn login(&self, login: &Login) -> Result<Session> {
+        let response = self
+            .agent
+            .post(self.url("/users/auth/login"))
+            .send_json(login)
+            .map_err(Error::Network)?;
+
+        // Wrong credentials, not an expired session.
+        let response = match check(response) {
+            Err(Error::AuthExpired) => {
+                return Err(Error::Validation("Wrong username or password".to_string()));
+            }
+            response => response?,
+        };
+
+        Ok(Session {
+            cookie: cookie(&response)?,
+            username: login.username.to_string(),
+        })
+    }
+
+    // Swaps the cookie for a fresh one.
+    pub fn renew(&self, cookie: &str) -> Result<Session> {
+        let response = self
+            .agent
+            .post(self.url("/users/auth/renew"))
+            .header("Cookie", cookie)
+            .send_empty()
+            .map_err(Error::Network)?;
+        let mut response = check(response)?;
+
+        let cookie = self::cookie(&response)?;
+        let username = read_json(&mut response)?;
+
+        Ok(Session { cookie, username })
+    }
+
+    pub fn logout(&self, cookie: &str) -> Result<()> {
+        let response = self
+            .agent
+            .post(self.url("/users/auth/logout"))
+            .header("Cookie", cookie)
+            .send_empty()
+            .map_err(Error::Network)?;
+        check(response)?;
+
+        Ok(())
+    }
+
+    // Every achievement of the game, named in `language` (a stardb language code).
+    pub fn achievements(&self, game: Game, language: &str) -> Result<Vec<Achievement>> {
+        let path = match game {
+            Game::Hsr => "/achievements",
+            Game::Gi => "/gi/achievements",
+            Game::Zzz => return Err(no_achievements()),
+        };
+
+        let response = self
+            .agent
+            .get(self.url(path))
+            .query("lang", language)
+            .call()
+            .map_err(Error::Network)?;
+
+        read_json(&mut check(response)?)
+    }
+
+    pub fn completed_achievements(&self, cookie: &str, game: Game) -> Result<Vec<u32>> {
+        let response = self
+            .agent
+            .get(self.achievements_url(game)?)
+            .header("Cookie", cookie)
+            .call()
+            .map_err(Error::Network)?;
+
+        read_json(&mut check(response)?)
+    }
+
+    pub fn add_achievements(&self, cookie: &str, game: Game, achievements: &[u32]) -> Result<()> {
+        let response = self
+            .agent
+            .put(self.achievements_url(game)?)
+            .header("Cookie", cookie)
+            .send_json(achievements)
+            .map_err(Error::Network)?;
+        check(response)?;
+
+        Ok(())
+    }
+
+    pub fn remove_achievements(
+        &self,
+        cookie: &str,
+        game: Game,
+        achievements: &[u32],
+    ) -> Result<()> {
+        let response = self
+            .agent
+            .delete(self.achievements_url(game)?)
+            .header("Cookie", cookie)
+            .force_send_body()
+            .send_json(achievements)
+            .map_err(Error::Network)?;
+        check(response)?;
+
+        Ok(())
+    }
+
+    // What a sync of the captured achievements would change on the account.
+    pub fn preview_sync(&self, cookie: &str, game: Game, captured: &[u32]) -> Result<SyncDiff> {
+        let completed = self.completed_achievements(cookie, game)?;
+        Ok(SyncDiff::new(&completed, captured))
+    }
+
+    // Sends only the changes. Additions go first, and are taken back if the
+    // removals fail, so that the account never ends up with less than before.
+    pub fn apply_sync(
+        &self,
+        cookie: &str,
+        game: Game,
+        diff: &SyncDiff,
+        mode: SyncMode,
+    ) -> Result<()> {
+        if !diff.to_add.is_empty() {
+            self.add_achievements(cookie, game, &diff.to_add)?;
+        }
+
+        if mode == SyncMode::Mirror
+            && !diff.to_remove.is_empty()
+            && let Err(e) = self.remove_achievements(cookie, game, &diff.to_remove)
+        {
+            if !diff.to_add.is_empty()
+                && let Err(rollback) = self.remove_achievements(cookie, game, &diff.to_add)
+            {
+                tracing::error!("Couldn't roll back the added achievements: {rollback}");
+            }
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    pub fn sync_achievements(
+        &self,
+        cookie: &str,
+        game: Game,
+        captured: &[u32],
+        mode: SyncMode,
+    ) -> Result<SyncDiff> {
+        let diff = self.preview_sync(cookie, game, captured)?;
+        self.apply_sync(cookie, game, &diff, mode)?;
+        Ok(diff)
+    }
+
+    // Without a cookie the pulls are imported anonymously.
+    pub fn import_pulls(&self, cookie: Option<&str>, game: Game, url: &str) -> Result<PullsImport> {
+        let path = match game {
+            Game::Hsr => "/warps-import",
+            Game::Gi => "/gi/wishes-import",
+            Game::Zzz => "/zzz/signals-import",
+        };
+
+        let mut request = self.agent.post(self.url(path));
+        if let Some(cookie) = cookie {
+            request = request.header("Cookie", cookie);
+        }
+        let response = request
+            .send_json(PullsImportRequest { url })
+            .map_err(Error::Network)?;
+
+        read_json(&mut check(response)?)
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    fn achievements_url(&self, game: Game) -> Result<String> {
+        let prefix = match game {
+            Game::Hsr => "",
+            Game::Gi => "gi/",
+            Game::Zzz => return Err(no_achievements()),
+        };
+
+        Ok(self.url(&format!("/users/me/{prefix}achievements/completed")))
+    }
+}
+
+fn no_achievements() -> Error {
+    Error::Validation("No achievements for this game".to_string())
+}
+
+fn check(mut response: Response<Body>) -> Result<Response<Body>> {
+    let status = response.status().as_u16();
+
+    match status {
+        200..=299 => Ok(response),
+        401 | 403 => Err(Error::AuthExpired),
+        400 | 422 => {
+            let message = response.body_mut().read_to_string().unwrap_or_default();
+            let message = match message.trim() {
+                "" => format!("status {status}"),
+                message => message.to_string(),
+            };
+            Err(Error::Validation(message))
+        }
+        status => Err(Error::Status(status)),
+    }
+}
+
+fn cookie(response: &Response<Body>) -> Result<String> {
+    response
+        .headers()
+        .get("Set-Cookie")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.split(';').next())
+        .map(|cookie| cookie.trim().to_string())
+        .filter(|cookie| !cookie.is_empty())
+        .ok_or_else(|| Error::InvalidResponse("No session cookie".to_string()))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(response: &mut Response<Body>) -> Result<T> {
+    response
+        .body_mut()
+        .read_json()
+        .map_err(|e| Error::InvalidResponse(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs_captured_against_completed() {
+        assert_eq!(
+            SyncDiff::new(&[3, 1, 2], &[4, 2, 3, 3]),
+            SyncDiff {
+                to_add: vec![4],
+                to_remove: vec![1],
+                unchanged: vec![2, 3],
+            }
+        );
+    }
+}
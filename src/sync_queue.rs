@@ -0,0 +1,324 @@
+// Stardb syncs that failed because stardb couldn't be reached, kept in the
+// storage dir until they go through. Retries back off from a minute up to
+// an hour. Jobs name the account they sync to and use its cookie at the time
+// of the retry, so a renewed cookie is picked up and none are stored here.
+// Jobs of an account that is logged out wait for it to log back in, only the
+// ones stardb rejects are dropped.
+// Pull urls hold an auth key, so the file is still encrypted with the
+// install's key file.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    games::Game,
+    snapshots,
+    stardb::{self, Client, SyncMode},
+    vault::Vault,
+};
+
+const FIRST_BACKOFF: u64 = 60;
+const MAX_BACKOFF: u64 = 60 * 60;
+
+// The app and its background retries share the file. Only held while
+// reading and writing it, never over the network.
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Job {
+    Achievements {
+        base_url: String,
+        game: Game,
+        username: String,
+        captured: Vec<u32>,
+        mode: SyncMode,
+    },
+    Pulls {
+        base_url: String,
+        game: Game,
+        // `None` imports without an account.
+        username: Option<String>,
+        url: String,
+    },
+}
+
+impl Job {
+    pub fn describe(&self) -> String {
+        match self {
+            Job::Achievements { game, .. } => format!("{} achievements sync", game.id()),
+            Job::Pulls { game, .. } => format!("{} pulls import", game.id()),
+        }
+    }
+
+    // `cookies` by username, of the accounts that are logged in. Jobs of an
+    // account that isn't fail like an expired session, and stay queued.
+    pub fn run(&self, cookies: &BTreeMap<String, String>) -> stardb::Result<()> {
+        let cookie = |username: &str| cookies.get(username).ok_or(stardb::Error::AuthExpired);
+
+        match self {
+            Job::Achievements {
+                base_url,
+                game,
+                username,
+                captured,
+                mode,
+            } => {
+                Client::new(base_url).sync_achievements(
+                    cookie(username)?,
+                    *game,
+                    captured,
+                    *mode,
+                )?;
+            }
+            Job::Pulls {
+                base_url,
+                game,
+                username,
+                url,
+            } => {
+                let cookie = username.as_deref().map(cookie).transpose()?;
+                Client::new(base_url).import_pulls(cookie.map(String::as_str), *game, url)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Entry {
+    // Tells the entries a retry ran apart from ones queued meanwhile.
+    id: u64,
+    job: Job,
+    attempts: u32,
+    next_attempt: u64,
+}
+
+#[derive(Default)]
+pub struct Retried {
+    pub synced: Vec<Job>,
+    // Rejected by stardb, so they won't be retried.
+    pub dropped: Vec<(Job, stardb::Error)>,
+    pub pending: usize,
+}
+
+// `dir` is the storage dir outside of tests.
+// Returns how many jobs are pending.
+pub fn push(dir: &Path, job: Job) -> anyhow::Result<usize> {
+    let _lock = LOCK.lock().unwrap();
+
+    let mut entries = load(dir)?;
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    let id = entries
+        .iter()
+        .map(|e| e.id + 1)
+        .max()
+        .unwrap_or_default()
+        .max(nanos);
+    entries.push(Entry {
+        id,
+        job,
+        attempts: 0,
+        next_attempt: snapshots::now() + FIRST_BACKOFF,
+    });
+    save(dir, &entries)?;
+
+    Ok(entries.len())
+}
+
+// Drops every queued job, they hold pull urls.
+pub fn forget(dir: &Path) -> anyhow::Result<()> {
+    let _lock = LOCK.lock().unwrap();
+    save(dir, &[])
+}
+
+// Runs the jobs that are due at `now` with `run`, `Job::run` outside of tests.
+// `force` ignores the backoff, e.g. on launch. The file is only locked to pick
+// the jobs and to write back the outcome, which keeps jobs queued or forgotten
+// in the meantime.
+pub fn retry(
+    dir: &Path,
+    now: u64,
+    force: bool,
+    run: impl Fn(&Job) -> stardb::Result<()>,
+) -> anyhow::Result<Retried> {
+    let due: Vec<Entry> = {
+        let _lock = LOCK.lock().unwrap();
+        load(dir)?
+            .into_iter()
+            .filter(|e| force || e.next_attempt <= now)
+            .collect()
+    };
+
+    let mut retried = Retried::default();
+    let mut failing = BTreeMap::new();
+    let mut done = BTreeSet::new();
+
+    for entry in due {
+        match run(&entry.job) {
+            Ok(()) => {
+                done.insert(entry.id);
+                retried.synced.push(entry.job);
+            }
+            Err(e) if rejected(&e) => {
+                done.insert(entry.id);
+                retried.dropped.push((entry.job, e));
+            }
+            // Expired sessions back off too, logging in retries right away.
+            Err(e) => {
+                tracing::info!("{} still failing: {e}", entry.job.describe());
+                failing.insert(entry.id, entry.attempts + 1);
+            }
+        }
+    }
+
+    let _lock = LOCK.lock().unwrap();
+
+    let mut entries = Vec::new();
+    for mut entry in load(dir)? {
+        if done.contains(&entry.id) {
+            continue;
+        }
+        if let Some(attempts) = failing.get(&entry.id) {
+            entry.attempts = *attempts;
+            entry.next_attempt = now + backoff(*attempts);
+        }
+        entries.push(entry);
+    }
+
+    save(dir, &entries)?;
+    retried.pending = entries.len();
+
+    Ok(retried)
+}
+
+// Retrying these would fail the same way. Rate limits and server errors pass.
+fn rejected(e: &stardb::Error) -> bool {
+    match e {
+        stardb::Error::Validation(_) => true,
+        stardb::Error::Status(status) => (400..500).contains(status) && *status != 429,
+        _ => false,
+    }
+}
+
+fn backoff(attempts: u32) -> u64 {
+    FIRST_BACKOFF
+        .saturating_mul(2u64.saturating_pow(attempts))
+        .min(MAX_BACKOFF)
+}
+
+fn vault(dir: &Path) -> Vault {
+    Vault::named(dir, "sync_queue")
+}
+
+fn load(dir: &Path) -> anyhow::Result<Vec<Entry>> {
+    Ok(vault(dir).load(None)?.unwrap_or_default())
+}
+
+fn save(dir: &Path, entries: &[Entry]) -> anyhow::Result<()> {
+    let path = dir.join("sync_queue.vault");
+    if entries.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+
+    vault(dir).save(&entries, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(dir: &Path) -> anyhow::Result<usize> {
+        Ok(load(dir)?.len())
+    }
+
+    fn pulls(url: &str) -> Job {
+        Job::Pulls {
+            base_url: stardb::BASE_URL.to_string(),
+            game: Game::Hsr,
+            username: None,
+            url: url.to_string(),
+        }
+    }
+
+    #[test]
+    fn keeps_jobs_until_stardb_is_reachable() {
+        let dir = std::env::temp_dir().join(format!("stardb-sync-queue-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(push(&dir, pulls("offline")).unwrap(), 1);
+        assert_eq!(push(&dir, pulls("expired")).unwrap(), 2);
+        assert_eq!(push(&dir, pulls("rejected")).unwrap(), 3);
+
+        let now = snapshots::now();
+
+        // Not due yet.
+        let retried = retry(&dir, now, false, |_| Ok(())).unwrap();
+        assert!(retried.synced.is_empty());
+        assert_eq!(retried.pending, 3);
+
+        let retried = retry(&dir, now, true, |job| match job {
+            Job::Pulls { url, .. } if url == "offline" => {
+                Err(stardb::Error::Network(ureq::Error::ConnectionFailed))
+            }
+            Job::Pulls { url, .. } if url == "expired" => Err(stardb::Error::AuthExpired),
+            _ => Err(stardb::Error::Validation("invalid url".to_string())),
+        })
+        .unwrap();
+        assert!(retried.synced.is_empty());
+        assert_eq!(retried.dropped.len(), 1);
+        assert_eq!(retried.dropped[0].0, pulls("rejected"));
+        assert_eq!(pending(&dir).unwrap(), 2);
+
+        // Jobs queued while a retry runs are kept.
+        let later = now + backoff(1);
+        let retried = retry(&dir, later, false, |job| {
+            if *job == pulls("offline") {
+                push(&dir, pulls("queued meanwhile")).unwrap();
+            }
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(retried.synced, [pulls("offline"), pulls("expired")]);
+        assert_eq!(retried.pending, 1);
+
+        forget(&dir).unwrap();
+        assert!(!dir.join("sync_queue.vault").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn looks_up_the_cookie_when_run() {
+        let job = Job::Achievements {
+            base_url: "http://127.0.0.1:9".to_string(),
+            game: Game::Hsr,
+            username: "kiana".to_string(),
+            captured: vec![1],
+            mode: SyncMode::AddOnly,
+        };
+
+        assert!(matches!(
+            job.run(&BTreeMap::new()),
+            Err(stardb::Error::AuthExpired)
+        ));
+    }
+
+    #[test]
+    fn backs_off_up_to_an_hour() {
+        assert_eq!(backoff(0), 60);
+        assert_eq!(backoff(1), 120);
+        assert_eq!(backoff(10), 3600);
+        assert_eq!(backoff(100), 3600);
+    }
+}
@@ -0,0 +1,405 @@
+// Background work of the app. Runs the library on a thread and reports back
+// through `Message`s.
+
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{Arc, mpsc},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    app::{self, Message, State},
+    data::{Language, Tables},
+    export::{self, hooks},
+    games::{Game, Progress, Session},
+    snapshots, sync_queue, vault, webhook,
+};
+
+pub fn achievements(
+    game: Game,
+    language: Language,
+    uid: Option<String>,
+    message_tx: &mpsc::Sender<Message>,
+) {
+    let message_tx = message_tx.clone();
+
+    thread::spawn(move || {
+        let achievements = match Session::achievements(game, language).wait(progress(&message_tx)) {
+            Ok(achievements) => achievements,
+            Err(e) => {
+                message_tx
+                    .send(Message::GoTo(State::Error(e.to_string())))
+                    .unwrap();
+                return;
+            }
+        };
+
+        // The game knows who is logged in better than the UID field.
+        let detected = game.uid();
+        if let Some(uid) = &detected {
+            message_tx.send(Message::Uid(game, uid.clone())).unwrap();
+            message_tx
+                .send(Message::DetectedUid(game, uid.clone(), None))
+                .unwrap();
+        }
+        let uid = detected.or(uid);
+        if let Some(uid) = &uid {
+            snapshot(
+                game,
+                uid,
+                snapshots::Data::Achievements(achievements.completed.clone()),
+                &message_tx,
+            );
+        }
+        let capture = snapshots::last::Capture::Achievements {
+            timestamp: snapshots::now(),
+            achievements: achievements.completed.clone(),
+            names: achievements.names.clone(),
+        };
+        if let Err(e) = snapshots::last::save(game, &capture) {
+            tracing::warn!("Couldn't cache capture: {e}");
+        }
+
+        message_tx
+            .send(Message::GoTo(State::Achievements(
+                achievements.completed,
+                achievements.names,
+            )))
+            .unwrap();
+    });
+}
+
+pub fn artifacts(
+    game: Game,
+    tables: Arc<Tables>,
+    uid: Option<String>,
+    message_tx: &mpsc::Sender<Message>,
+) {
+    let message_tx = message_tx.clone();
+
+    thread::spawn(move || {
+        let artifacts = match Session::artifacts(game, tables).wait(progress(&message_tx)) {
+            Ok(artifacts) => artifacts,
+            Err(e) => {
+                message_tx
+                    .send(Message::GoTo(State::Error(e.to_string())))
+                    .unwrap();
+                return;
+            }
+        };
+
+        // The game knows who is logged in better than the UID field.
+        let detected = game.uid();
+        if let Some(uid) = &detected {
+            message_tx.send(Message::Uid(game, uid.clone())).unwrap();
+            message_tx
+                .send(Message::DetectedUid(game, uid.clone(), None))
+                .unwrap();
+        }
+        let uid = detected.or(uid);
+        if let Some(uid) = &uid {
+            snapshot(
+                game,
+                uid,
+                snapshots::Data::Artifacts(artifacts.artifacts.clone()),
+                &message_tx,
+            );
+        }
+        let timestamp = snapshots::now();
+        let capture = snapshots::last::Capture::Artifacts {
+            timestamp,
+            artifacts: artifacts.artifacts.clone(),
+            warnings: artifacts.warnings.clone(),
+        };
+        if let Err(e) = snapshots::last::save(game, &capture) {
+            tracing::warn!("Couldn't cache capture: {e}");
+        }
+
+        message_tx
+            .send(Message::GoTo(State::Artifacts(app::Artifacts::new(
+                artifacts.artifacts,
+                artifacts.warnings,
+                timestamp,
+            ))))
+            .unwrap();
+    });
+}
+
+fn progress(message_tx: &mpsc::Sender<Message>) -> impl FnMut(Progress) {
+    move |progress| message_tx.send(Message::Progress(progress)).unwrap()
+}
+
+fn snapshot(game: Game, uid: &str, data: snapshots::Data, message_tx: &mpsc::Sender<Message>) {
+    if let Err(e) = snapshots::save(game, uid, data) {
+        tracing::warn!("Couldn't save snapshot: {e}");
+        message_tx
+            .send(Message::Toast(egui_notify::Toast::warning(format!(
+                "Couldn't save snapshot: {e}"
+            ))))
+            .unwrap();
+    }
+}
+
+pub fn pulls(game: Game, path: PathBuf, message_tx: &mpsc::Sender<Message>) {
+    let message_tx = message_tx.clone();
+
+    thread::spawn(move || {
+        match game.locate_gacha_url(Some(&path)) {
+            Ok(url) => message_tx.send(Message::GoTo(State::Pulls(url))),
+            Err(e) => message_tx.send(Message::GoTo(State::Error(e.to_string()))),
+        }
+        .unwrap()
+    });
+}
+
+pub fn load_tables(message_tx: &mpsc::Sender<Message>) {
+    let message_tx = message_tx.clone();
+
+    thread::spawn(move || {
+        let tables = crate::storage_dir().and_then(|storage| Tables::load(&storage));
+        match tables {
+            Ok(tables) => message_tx.send(Message::Tables(Arc::new(tables))).unwrap(),
+            Err(e) => message_tx
+                .send(Message::Toast(egui_notify::Toast::error(format!(
+                    "Error loading data tables: {e}"
+                ))))
+                .unwrap(),
+        }
+    });
+}
+
+pub fn import_tables(dir: PathBuf, message_tx: &mpsc::Sender<Message>) {
+    let message_tx = message_tx.clone();
+
+    thread::spawn(move || {
+        match crate::storage_dir().and_then(|storage| Tables::import(&dir, &storage)) {
+            Ok(tables) => {
+                message_tx
+                    .send(Message::Toast(egui_notify::Toast::success(format!(
+                        "Imported data {}",
+                        tables.version
+                    ))))
+                    .unwrap();
+                message_tx.send(Message::Tables(Arc::new(tables))).unwrap();
+            }
+            Err(e) => message_tx
+                .send(Message::Toast(egui_notify::Toast::error(format!(
+                    "Error importing data: {e}"
+                ))))
+                .unwrap(),
+        }
+    });
+}
+
+pub fn snapshot_report(
+    game: Game,
+    uid: String,
+    language: Language,
+    message_tx: &mpsc::Sender<Message>,
+) {
+    let message_tx = message_tx.clone();
+
+    thread::spawn(move || match snapshots::report(game, &uid, language) {
+        Ok(report) => message_tx
+            .send(Message::GoTo(State::Snapshots(Box::new(report))))
+            .unwrap(),
+        Err(e) => message_tx
+            .send(Message::GoTo(State::Error(e.to_string())))
+            .unwrap(),
+    });
+}
+
+// Rebuilds the result screen of the cached capture.
+pub fn restore_capture(game: Game, kind: snapshots::Kind, message_tx: &mpsc::Sender<Message>) {
+    let message_tx = message_tx.clone();
+
+    thread::spawn(move || match snapshots::last::load(game, kind) {
+        Ok(capture) => {
+            message_tx
+                .send(Message::Toast(egui_notify::Toast::info(format!(
+                    "Loaded capture from {}",
+                    export::format_timestamp(capture.timestamp())
+                ))))
+                .unwrap();
+
+            let state = match capture {
+                snapshots::last::Capture::Artifacts {
+                    timestamp,
+                    artifacts,
+                    warnings,
+                } => State::Artifacts(app::Artifacts::new(artifacts, warnings, timestamp)),
+                snapshots::last::Capture::Achievements {
+                    achievements,
+                    names,
+                    ..
+                } => State::Achievements(achievements, names),
+            };
+            message_tx.send(Message::Restored(state)).unwrap();
+        }
+        Err(e) => {
+            message_tx.send(Message::GoTo(State::Game)).unwrap();
+            message_tx
+                .send(Message::Toast(egui_notify::Toast::error(e.to_string())))
+                .unwrap();
+        }
+    });
+}
+
+// Runs the post-export hooks one after another, with a toast for each.
+pub fn run_hooks(
+    hooks: String,
+    file: PathBuf,
+    game: Game,
+    kind: String,
+    uid: Option<String>,
+    message_tx: &mpsc::Sender<Message>,
+) {
+    if hooks::commands(&hooks).next().is_none() {
+        return;
+    }
+
+    let message_tx = message_tx.clone();
+
+    thread::spawn(move || {
+        let export = hooks::Export {
+            file: &file,
+            game,
+            kind: &kind,
+            uid: uid.as_deref(),
+        };
+
+        for command in hooks::commands(&hooks) {
+            let toast = match hooks::run(command, &export) {
+                Ok(outcome) => {
+                    tracing::info!("Hook {command}: {}\n{}", outcome.status, outcome.output);
+
+                    let mut text = format!("{command}: {}", outcome.status);
+                    if !outcome.output.is_empty() {
+                        // Only the end fits in a toast, the log has everything.
+                        let output: Vec<_> = outcome.output.lines().rev().take(5).collect();
+                        text.push('\n');
+                        text.push_str(&output.into_iter().rev().collect::<Vec<_>>().join("\n"));
+                    }
+
+                    if outcome.success {
+                        egui_notify::Toast::success(text)
+                    } else {
+                        egui_notify::Toast::error(text)
+                    }
+                }
+                Err(e) => egui_notify::Toast::error(format!("{command}: {e}")),
+            };
+
+            message_tx.send(Message::Toast(toast)).unwrap();
+        }
+    });
+}
+
+pub fn push_webhook(
+    webhook: webhook::Webhook,
+    game: Game,
+    kind: &'static str,
+    uid: Option<String>,
+    data: serde_json::Value,
+    message_tx: &mpsc::Sender<Message>,
+) {
+    let message_tx = message_tx.clone();
+
+    thread::spawn(move || {
+        let sent = webhook
+            .payload(game, kind, uid.as_deref(), snapshots::now(), &data)
+            .and_then(|payload| webhook.send(&payload));
+
+        match sent {
+            Ok(()) => tracing::info!("Sent {kind} to the webhook"),
+            Err(e) => message_tx
+                .send(Message::Toast(egui_notify::Toast::error(format!(
+                    "Error sending {kind} to the webhook: {e}"
+                ))))
+                .unwrap(),
+        }
+    });
+}
+
+// Keeps a sync that couldn't reach stardb for `retry_syncs`.
+pub fn queue_sync(job: sync_queue::Job, message_tx: &mpsc::Sender<Message>) {
+    let pushed = crate::storage_dir().and_then(|dir| sync_queue::push(&dir, job.clone()));
+    let toast = match pushed {
+        Ok(pending) => {
+            message_tx.send(Message::PendingSyncs(pending)).unwrap();
+            egui_notify::Toast::warning(format!(
+                "Couldn't reach stardb, the {} will be retried",
+                job.describe()
+            ))
+        }
+        Err(e) => egui_notify::Toast::error(format!("Couldn't queue the {}: {e}", job.describe())),
+    };
+    message_tx.send(Message::Toast(toast)).unwrap();
+}
+
+// Asks the app to retry the queued syncs every now and then, see
+// `App::retry_syncs`.
+pub fn tick_sync_retries(message_tx: &mpsc::Sender<Message>) {
+    let message_tx = message_tx.clone();
+
+    thread::spawn(move || {
+        loop {
+            message_tx.send(Message::RetrySyncs).unwrap();
+            thread::sleep(Duration::from_secs(30));
+        }
+    });
+}
+
+// Runs the due syncs with `cookies` by username. `force` ignores the backoff.
+pub fn retry_syncs(
+    cookies: BTreeMap<String, String>,
+    force: bool,
+    message_tx: &mpsc::Sender<Message>,
+) {
+    let message_tx = message_tx.clone();
+
+    thread::spawn(move || {
+        let retried = crate::storage_dir().and_then(|dir| {
+            sync_queue::retry(&dir, snapshots::now(), force, |job| job.run(&cookies))
+        });
+
+        let pending = match retried {
+            Ok(retried) => {
+                for job in retried.synced {
+                    message_tx
+                        .send(Message::Toast(egui_notify::Toast::success(format!(
+                            "Retried {}",
+                            job.describe()
+                        ))))
+                        .unwrap();
+                }
+                for (job, e) in retried.dropped {
+                    message_tx
+                        .send(Message::Toast(egui_notify::Toast::error(format!(
+                            "Gave up on {}: {e}",
+                            job.describe()
+                        ))))
+                        .unwrap();
+                }
+                Some(retried.pending)
+            }
+            Err(e) => {
+                tracing::warn!("Couldn't retry queued syncs: {e}");
+                None
+            }
+        };
+
+        message_tx.send(Message::SyncsRetried(pending)).unwrap();
+    });
+}
+
+// Wipes the session, queued syncs with their pull urls, and every snapshot.
+pub fn forget_everything(vault: Option<&vault::Vault>) -> anyhow::Result<()> {
+    if let Some(vault) = vault {
+        vault.forget()?;
+    }
+    sync_queue::forget(&crate::storage_dir()?)?;
+    snapshots::forget()
+}
@@ -1,17 +1,11 @@
-use std::thread;
+use std::{collections::BTreeMap, thread};
 
 use crate::{
-    app::{App, Message, State},
-    games,
+    app::{App, Message, State, User},
+    export, stardb, sync_queue, tasks,
 };
 
-pub fn show(ui: &mut egui::Ui, achievements: &[u32], app: &App) {
-    let key = match app.game {
-        games::Game::Hsr => "hsr_achievements",
-        games::Game::Gi => "gi_achievements",
-        _ => unimplemented!(),
-    };
-
+pub fn show(ui: &mut egui::Ui, achievements: &[u32], names: &BTreeMap<u32, String>, app: &App) {
     ui.label("Finished");
 
     if ui
@@ -21,120 +15,169 @@ pub fn show(ui: &mut egui::Ui, achievements: &[u32], app: &App) {
         ))
         .clicked()
     {
-        if let Err(e) = arboard::Clipboard::new()
-            .and_then(|mut c| c.set_text(serde_json::json!({ key: achievements }).to_string()))
-        {
-            app.message_tx
-                .send(Message::GoTo(State::Error(e.to_string())))
-                .unwrap();
-        } else {
-            app.message_tx
-                .send(Message::Toast(egui_notify::Toast::success("Copied")))
-                .unwrap();
+        let json = export::stardb_achievements(app.game, achievements)
+            .map(|bytes| String::from_utf8_lossy(&bytes).to_string());
+        match json.and_then(|json| {
+            arboard::Clipboard::new().and_then(|mut c| c.set_text(json.clone()))?;
+            Ok(json)
+        }) {
+            Ok(json) => {
+                app.message_tx
+                    .send(Message::Toast(egui_notify::Toast::success("Copied")))
+                    .unwrap();
+                super::export::copied("achievements", "json", json.as_bytes(), app);
+            }
+            Err(e) => {
+                app.message_tx
+                    .send(Message::GoTo(State::Error(e.to_string())))
+                    .unwrap();
+            }
         }
     }
 
+    if ui.button("Save as...").clicked() {
+        super::export::save_as(
+            "achievements",
+            "json",
+            || export::stardb_achievements(app.game, achievements),
+            app,
+        );
+    }
+
     ui.hyperlink_to("Click here to import", "https://stardb.gg/import");
 
-    if let Some(user) = &app.user
-        && ui
-            .button(format!("Sync to account: \"{}\"", user.username))
-            .clicked()
-    {
+    super::export::buttons(
+        ui,
+        "achievements",
+        || export::achievements(achievements, names),
+        app,
+    );
+
+    let uid = app.uids.get(app.game);
+    if let Some(username) = app.accounts.logged_out(app.game, uid.as_deref()) {
+        ui.label(format!("Log in as \"{username}\" to sync this UID"));
+    } else if let Some(user) = app.accounts.for_uid(app.game, uid.as_deref()) {
+        match &app.sync_preview {
+            Some(diff) => sync_preview(ui, diff, names, user, app),
+            None => {
+                if ui
+                    .button(format!("Sync to account: \"{}\"", user.username))
+                    .clicked()
+                {
+                    preview(&user.id, achievements, app);
+                }
+            }
+        }
+    }
+
+    ui.separator();
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for id in achievements {
+            match names.get(id) {
+                Some(name) => ui.label(name),
+                None => ui.label(id.to_string()),
+            };
+        }
+    });
+}
+
+fn preview(cookie: &str, achievements: &[u32], app: &App) {
+    let message_tx = app.message_tx.clone();
+    let game = app.game;
+    let cookie = cookie.to_string();
+    let achievements = achievements.to_vec();
+
+    thread::spawn(move || {
+        match stardb::Client::default().preview_sync(&cookie, game, &achievements) {
+            Ok(diff) => message_tx.send(Message::SyncPreview(Some(diff))).unwrap(),
+            Err(e) => message_tx
+                .send(Message::Toast(egui_notify::Toast::error(format!(
+                    "Error: {e}"
+                ))))
+                .unwrap(),
+        }
+    });
+}
+
+fn sync_preview(
+    ui: &mut egui::Ui,
+    diff: &stardb::SyncDiff,
+    names: &BTreeMap<u32, String>,
+    user: &User,
+    app: &App,
+) {
+    let list = |ui: &mut egui::Ui, title: String, ids: &[u32]| {
+        ui.collapsing(title, |ui| {
+            for id in ids {
+                match names.get(id) {
+                    Some(name) => ui.label(name),
+                    None => ui.label(id.to_string()),
+                };
+            }
+        });
+    };
+
+    list(ui, format!("{} to add", diff.to_add.len()), &diff.to_add);
+    list(
+        ui,
+        format!("{} to remove (mirror only)", diff.to_remove.len()),
+        &diff.to_remove,
+    );
+    list(
+        ui,
+        format!("{} unchanged", diff.unchanged.len()),
+        &diff.unchanged,
+    );
+
+    ui.horizontal(|ui| {
+        // Every button is drawn each frame, whichever was clicked.
+        let add_only = ui.button("Add only").clicked();
+        let mirror = ui.button("Mirror").clicked();
+        let cancel = ui.button("Cancel").clicked();
+
+        let mode = if add_only {
+            stardb::SyncMode::AddOnly
+        } else if mirror {
+            stardb::SyncMode::Mirror
+        } else {
+            if cancel {
+                app.message_tx.send(Message::SyncPreview(None)).unwrap();
+            }
+            return;
+        };
+
+        app.message_tx.send(Message::SyncPreview(None)).unwrap();
         app.message_tx
             .send(Message::Toast(egui_notify::Toast::info("Syncing")))
             .unwrap();
 
-        let prefix = match app.game {
-            games::Game::Hsr => "",
-            games::Game::Gi => "gi/",
-            _ => unimplemented!(),
-        };
+        let message_tx = app.message_tx.clone();
+        let game = app.game;
+        let cookie = user.id.clone();
+        let username = user.username.clone();
+        let diff = diff.clone();
 
-        let url = format!("https://stardb.gg/api/users/me/{prefix}achievements/completed");
-
-        {
-            let message_tx = app.message_tx.clone();
-            let id = user.id.clone();
-            let achievements = achievements.to_vec();
-
-            thread::spawn(move || {
-                let to_delete: Vec<i32> = match ureq::get(&url).header("Cookie", &id).call() {
-                    Ok(r) => {
-                        if r.status() == 200 {
-                            r.into_body().read_json().unwrap()
-                        } else {
-                            message_tx
-                                .send(Message::Toast(egui_notify::Toast::error(
-                                    "Error. Try Relogging",
-                                )))
-                                .unwrap();
-
-                            return;
-                        }
-                    }
-                    Err(e) => {
-                        message_tx
-                            .send(Message::Toast(egui_notify::Toast::error(format!(
-                                "Error: {e}"
-                            ))))
-                            .unwrap();
-                        return;
-                    }
-                };
+        thread::spawn(move || {
+            let client = stardb::Client::default();
+            let toast = match client.apply_sync(&cookie, game, &diff, mode) {
+                Ok(()) => egui_notify::Toast::success("Synced"),
+                Err(stardb::Error::Network(_)) => {
+                    let mut captured = diff.to_add;
+                    captured.extend(diff.unchanged);
 
-                match ureq::delete(&url)
-                    .header("Cookie", &id)
-                    .force_send_body()
-                    .send_json(to_delete)
-                {
-                    Ok(r) => {
-                        if r.status() != 200 {
-                            message_tx
-                                .send(Message::Toast(egui_notify::Toast::error(
-                                    "Error. Try Relogging",
-                                )))
-                                .unwrap();
-
-                            return;
-                        }
-                    }
-                    Err(e) => {
-                        message_tx
-                            .send(Message::Toast(egui_notify::Toast::error(format!(
-                                "Error: {e}"
-                            ))))
-                            .unwrap();
-                        return;
-                    }
-                };
-
-                match ureq::put(&url)
-                    .header("Cookie", &id)
-                    .send_json(achievements)
-                {
-                    Ok(r) => {
-                        if r.status() == 200 {
-                            message_tx
-                                .send(Message::Toast(egui_notify::Toast::success("Synced")))
-                                .unwrap();
-                        } else {
-                            message_tx
-                                .send(Message::Toast(egui_notify::Toast::error(
-                                    "Error. Try Relogging",
-                                )))
-                                .unwrap();
-                        }
-                    }
-                    Err(e) => {
-                        message_tx
-                            .send(Message::Toast(egui_notify::Toast::error(format!(
-                                "Error: {e}"
-                            ))))
-                            .unwrap();
-                    }
+                    let job = sync_queue::Job::Achievements {
+                        base_url: client.base_url().to_string(),
+                        game,
+                        username,
+                        captured,
+                        mode,
+                    };
+                    return tasks::queue_sync(job, &message_tx);
                 }
-            });
-        }
-    }
+                Err(e) => egui_notify::Toast::error(format!("Error: {e}")),
+            };
+            message_tx.send(Message::Toast(toast)).unwrap();
+        });
+    });
 }
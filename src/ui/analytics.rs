@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+
+use super::artifacts::{set_name, stat_name};
+use crate::{
+    app::{App, Message},
+    export,
+    games::{
+        self,
+        analytics::{self, Counts, Metrics, Weights},
+    },
+};
+
+const DEFAULT_FODDER_THRESHOLD: f64 = 0.3;
+
+pub fn show(ui: &mut egui::Ui, artifacts: &[games::Artifact], app: &App) {
+    let id = ui.id().with("fodder_threshold");
+    let mut threshold: f64 = ui
+        .data_mut(|d| d.get_temp(id))
+        .unwrap_or(DEFAULT_FODDER_THRESHOLD);
+
+    let metrics: Vec<_> = artifacts
+        .iter()
+        .map(|a| Metrics::new(a, &app.weights))
+        .collect();
+    let fodder: Vec<_> = (0..artifacts.len())
+        .filter(|i| analytics::is_fodder(&artifacts[*i], &metrics[*i], threshold))
+        .collect();
+
+    egui::ScrollArea::vertical()
+        .id_salt("analytics")
+        .show(ui, |ui| {
+            ui.collapsing("Substat weights", |ui| {
+                let mut weights = app.weights.clone();
+
+                egui::Grid::new("weights_grid").show(ui, |ui| {
+                    for key in Weights::SUBSTATS {
+                        let mut weight = weights.get(key);
+                        ui.label(stat_name(key, app));
+                        if ui
+                            .add(egui::Slider::new(&mut weight, 0.0..=1.0).step_by(0.05))
+                            .changed()
+                        {
+                            weights.0.insert(key, weight);
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                if ui.button("Reset").clicked() {
+                    weights = Weights::default();
+                }
+
+                if weights != app.weights {
+                    app.message_tx.send(Message::Weights(weights)).unwrap();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Fodder below potential efficiency:");
+                ui.add(egui::Slider::new(&mut threshold, 0.0..=1.0).step_by(0.05));
+            });
+
+            super::export::buttons(
+                ui,
+                "artifact_analytics",
+                || export::analytics(artifacts, &app.weights, threshold),
+                app,
+            );
+
+            ui.separator();
+
+            let counts = Counts::new(artifacts);
+            ui.collapsing("By set", |ui| {
+                counts_grid(
+                    ui,
+                    "sets",
+                    counts.sets.iter().map(|(k, n)| (set_name(*k, app), *n)),
+                );
+            });
+            ui.collapsing("By slot", |ui| {
+                counts_grid(
+                    ui,
+                    "slots",
+                    counts.slots.iter().map(|(k, n)| (k.to_string(), *n)),
+                );
+            });
+            ui.collapsing("By main stat", |ui| {
+                counts_grid(
+                    ui,
+                    "main_stats",
+                    counts
+                        .main_stats
+                        .iter()
+                        .map(|(k, n)| (stat_name(*k, app), *n)),
+                );
+            });
+
+            ui.collapsing(format!("Likely fodder ({})", fodder.len()), |ui| {
+                metrics_grid(ui, "fodder", &fodder, artifacts, &metrics, app);
+            });
+
+            ui.separator();
+
+            let mut rows: Vec<_> = (0..artifacts.len()).collect();
+            rows.sort_by(|a, b| metrics[*b].potential.total_cmp(&metrics[*a].potential));
+            metrics_grid(ui, "metrics", &rows, artifacts, &metrics, app);
+        });
+
+    ui.data_mut(|d| d.insert_temp(id, threshold));
+}
+
+fn counts_grid(ui: &mut egui::Ui, id: &str, counts: impl Iterator<Item = (String, usize)>) {
+    let counts: BTreeMap<_, _> = counts.collect();
+
+    egui::Grid::new(id).striped(true).show(ui, |ui| {
+        for (name, count) in counts {
+            ui.label(name);
+            ui.label(count.to_string());
+            ui.end_row();
+        }
+    });
+}
+
+fn metrics_grid(
+    ui: &mut egui::Ui,
+    id: &str,
+    rows: &[usize],
+    artifacts: &[games::Artifact],
+    metrics: &[Metrics],
+    app: &App,
+) {
+    egui::Grid::new(id).striped(true).show(ui, |ui| {
+        for title in [
+            "Set",
+            "Slot",
+            "Main stat",
+            "Level",
+            "Crit value",
+            "Roll value",
+            "Efficiency",
+            "Potential",
+        ] {
+            ui.strong(title);
+        }
+        ui.end_row();
+
+        for i in rows {
+            let (artifact, metrics) = (&artifacts[*i], &metrics[*i]);
+
+            ui.label(set_name(artifact.setKey, app));
+            ui.label(artifact.slotKey.as_str());
+            ui.label(stat_name(artifact.mainStatKey, app));
+            ui.label(format!("+{}", artifact.level));
+            ui.label(format!("{:.1}", metrics.crit_value));
+            ui.label(format!("{:.2} / {}", metrics.roll_value, metrics.rolls));
+            ui.label(format!("{:.0}%", metrics.efficiency * 100.0));
+            ui.label(format!("{:.2} / {}", metrics.potential, metrics.max_rolls));
+            ui.end_row();
+        }
+    });
+}
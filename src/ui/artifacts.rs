@@ -1,48 +1,523 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use egui_remixicon::icons;
+
 use crate::{
-    app::{App, Message, State},
-    games,
+    app::{self, App, Message, State},
+    data, export, games,
 };
 
-#[derive(serde::Serialize)]
-#[allow(non_snake_case)]
-pub struct GOOD<'a> {
-    format: &'a str,
-    version: u32,
-    source: &'a str,
-    artifacts: &'a Vec<games::Artifact>,
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Tab {
+    #[default]
+    Table,
+    Analytics,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Column {
+    #[default]
+    Set,
+    Slot,
+    MainStat,
+    Level,
+    Rarity,
+    Lock,
+    // By stat, then value.
+    Substat(usize),
+}
+
+impl Column {
+    const ALL: [Column; 6] = [
+        Column::Set,
+        Column::Slot,
+        Column::MainStat,
+        Column::Level,
+        Column::Rarity,
+        Column::Lock,
+    ];
+
+    fn title(self) -> String {
+        match self {
+            Column::Set => "Set".to_string(),
+            Column::Slot => "Slot".to_string(),
+            Column::MainStat => "Main stat".to_string(),
+            Column::Level => "Level".to_string(),
+            Column::Rarity => "Rarity".to_string(),
+            Column::Lock => "Lock".to_string(),
+            Column::Substat(i) => format!("Substat {}", i + 1),
+        }
+    }
+}
+
+// View state of the table, kept in egui memory so it survives between frames
+// without cloning the artifacts into a new `State`.
+#[derive(Clone, Default)]
+struct Table {
+    tab: Tab,
+    search: String,
+    sort: Column,
+    descending: bool,
+    // Indices into the captured artifacts.
+    selected: BTreeSet<usize>,
+    // Rows for the search and sort they were computed with.
+    rows: Option<(Order, Vec<usize>)>,
+    // Exports artifacts that failed validation too.
+    export_invalid: bool,
+}
+
+#[derive(Clone, PartialEq)]
+struct Order {
+    search: String,
+    sort: Column,
+    descending: bool,
+    language: data::Language,
+}
+
+// Display names of the keys of a capture, looked up once instead of in every
+// comparison.
+struct Names {
+    sets: BTreeMap<games::SetKey, String>,
+    stats: BTreeMap<games::StatKey, String>,
 }
 
-pub fn show(ui: &mut egui::Ui, artifacts: &Vec<games::Artifact>, app: &App) {
+pub fn show(ui: &mut egui::Ui, capture: &app::Artifacts, app: &App) {
     match app.game {
         games::Game::Gi => "gi_artifacts",
         _ => unimplemented!(),
     };
 
+    let artifacts = &capture.artifacts[..];
+    let warnings = &capture.warnings;
+    let problems = &capture.problems;
+
+    // Per capture, so that selected indices never point into another one.
+    let id = ui.id().with(("artifacts_table", capture.timestamp));
+    let mut table: Table = ui.data_mut(|d| d.get_temp(id)).unwrap_or_default();
+
     ui.label("Finished");
 
     if ui
-        .button(format!(
-            "Copy {} artifacts to clipboard",
-            artifacts.len()
-        ))
+        .button(format!("Copy {} artifacts to clipboard", artifacts.len()))
+        .clicked()
+    {
+        copy(artifacts, table.export_invalid, app);
+    }
+
+    if ui.button("Save as...").clicked() {
+        super::export::save_as(
+            "artifacts",
+            "json",
+            || export::good(artifacts, table.export_invalid),
+            app,
+        );
+    }
+
+    if ui.button("Merge into GOOD database...").clicked() {
+        merge(artifacts, table.export_invalid, app);
+    }
+
+    super::export::buttons(
+        ui,
+        "artifacts",
+        || export::artifacts(artifacts, app.tables.as_deref(), app.language),
+        app,
+    );
+
+    if !problems.is_empty() {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!(
+                "{} artifacts failed validation and may be rejected by optimizers:",
+                problems.len()
+            ),
+        );
+
+        egui::ScrollArea::vertical()
+            .id_salt("problems")
+            .max_height(80.0)
+            .show(ui, |ui| {
+                for (i, problems) in problems {
+                    let artifact = &artifacts[*i];
+                    ui.label(format!(
+                        "{} {} +{}: {}",
+                        set_name(artifact.setKey, app),
+                        artifact.slotKey,
+                        artifact.level,
+                        problems.join(", ")
+                    ));
+                }
+            });
+
+        ui.checkbox(&mut table.export_invalid, "Export them anyway");
+    }
+
+    if !warnings.is_empty() {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!("{} artifacts were skipped:", warnings.len()),
+        );
+
+        egui::ScrollArea::vertical()
+            .id_salt("warnings")
+            .max_height(80.0)
+            .show(ui, |ui| {
+                for warning in warnings {
+                    ui.label(warning);
+                }
+            });
+    }
+
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        ui.selectable_value(&mut table.tab, Tab::Table, "Table");
+        ui.selectable_value(&mut table.tab, Tab::Analytics, "Analytics");
+    });
+
+    if table.tab == Tab::Analytics {
+        super::analytics::show(ui, artifacts, app);
+        ui.data_mut(|d| d.insert_temp(id, table));
+        return;
+    }
+
+    let selected: Vec<_> = table
+        .selected
+        .iter()
+        .filter_map(|i| artifacts.get(*i).cloned())
+        .collect();
+
+    ui.horizontal(|ui| {
+        ui.label("Search:");
+        ui.text_edit_singleline(&mut table.search);
+
+        ui.add_enabled_ui(!selected.is_empty(), |ui| {
+            if ui
+                .button(format!("Copy {} selected", selected.len()))
+                .clicked()
+            {
+                copy(&selected, table.export_invalid, app);
+            }
+
+            if ui.button("Save selected as...").clicked() {
+                super::export::save_as(
+                    "artifacts",
+                    "json",
+                    || export::good(&selected, table.export_invalid),
+                    app,
+                );
+            }
+
+            if ui.button("Clear selection").clicked() {
+                table.selected.clear();
+            }
+        });
+    });
+
+    let order = Order {
+        search: table.search.clone(),
+        sort: table.sort,
+        descending: table.descending,
+        language: app.language,
+    };
+    let rows = match table.rows.take() {
+        Some((cached, rows)) if cached == order => rows,
+        _ => self::rows(artifacts, &order, app),
+    };
+
+    ui.horizontal(|ui| {
+        ui.label(format!("Showing {} of {}", rows.len(), artifacts.len()));
+
+        if ui.button("Select shown").clicked() {
+            table.selected.extend(rows.iter().copied());
+        }
+    });
+
+    let shown = rows.clone();
+    table.rows = Some((order, rows));
+
+    egui::ScrollArea::both()
+        .id_salt("artifacts")
+        .show(ui, |ui| {
+            egui::Grid::new("artifacts_grid")
+                .striped(true)
+                .num_columns(Column::ALL.len() + 6)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.label("");
+                    for column in Column::ALL {
+                        header(ui, &mut table, column);
+                    }
+                    for i in 0..4 {
+                        header(ui, &mut table, Column::Substat(i));
+                    }
+                    ui.end_row();
+
+                    for i in shown {
+                        let artifact = &artifacts[i];
+
+                        let mut checked = table.selected.contains(&i);
+                        if ui.checkbox(&mut checked, "").changed() {
+                            if checked {
+                                table.selected.insert(i);
+                            } else {
+                                table.selected.remove(&i);
+                            }
+                        }
+
+                        match problems.get(&i) {
+                            Some(problems) => {
+                                ui.colored_label(
+                                    ui.visuals().warn_fg_color,
+                                    icons::ERROR_WARNING_LINE,
+                                )
+                                .on_hover_text(problems.join("\n"));
+                            }
+                            None => {
+                                ui.label("");
+                            }
+                        }
+
+                        ui.label(set_name(artifact.setKey, app));
+                        ui.label(artifact.slotKey.as_str());
+                        ui.label(stat_name(artifact.mainStatKey, app));
+                        ui.label(format!("+{}", artifact.level));
+                        ui.label(artifact.rarity.to_string());
+                        ui.label(if artifact.lock { "Locked" } else { "" });
+                        for i in 0..4 {
+                            match artifact.substats.get(i) {
+                                Some(substat) => ui.label(substat_text(substat, app)),
+                                None => ui.label(""),
+                            };
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+    ui.data_mut(|d| d.insert_temp(id, table));
+}
+
+fn header(ui: &mut egui::Ui, table: &mut Table, column: Column) {
+    let arrow = match (table.sort == column, table.descending) {
+        (false, _) => "",
+        (true, false) => icons::ARROW_UP_S_LINE,
+        (true, true) => icons::ARROW_DOWN_S_LINE,
+    };
+
+    if ui
+        .selectable_label(table.sort == column, format!("{} {arrow}", column.title()))
         .clicked()
     {
-        if let Err(e) = arboard::Clipboard::new()
-            .and_then(|mut c| c.set_text(serde_json::json!(
-                GOOD{
-                    format: "GOOD",
-                    version: 2,
-                    source: "stardb-exporter",
-                    artifacts: artifacts
-                }).to_string()))
-        {
+        if table.sort == column {
+            table.descending = !table.descending;
+        } else {
+            table.sort = column;
+            table.descending = false;
+        }
+    }
+}
+
+// Indices of the artifacts matching the search, in table order.
+fn rows(artifacts: &[games::Artifact], order: &Order, app: &App) -> Vec<usize> {
+    let names = names(artifacts, app);
+    let search = order.search.to_lowercase();
+
+    let mut rows: Vec<usize> = (0..artifacts.len())
+        .filter(|i| search.is_empty() || search_text(&artifacts[*i], &names).contains(&search))
+        .collect();
+
+    rows.sort_by(|a, b| {
+        let (a, b) = (&artifacts[*a], &artifacts[*b]);
+        let ordering = match order.sort {
+            Column::Set => names.sets[&a.setKey].cmp(&names.sets[&b.setKey]),
+            Column::Slot => a.slotKey.cmp(&b.slotKey),
+            Column::MainStat => names.stats[&a.mainStatKey].cmp(&names.stats[&b.mainStatKey]),
+            Column::Level => a.level.cmp(&b.level),
+            Column::Rarity => a.rarity.cmp(&b.rarity),
+            Column::Lock => a.lock.cmp(&b.lock),
+            Column::Substat(i) => {
+                let substat = |artifact: &games::Artifact| {
+                    let substat = artifact.substats.get(i)?;
+                    Some((&names.stats[&substat.key], substat.value))
+                };
+                match (substat(a), substat(b)) {
+                    (Some((a_name, a_value)), Some((b_name, b_value))) => {
+                        a_name.cmp(b_name).then(a_value.total_cmp(&b_value))
+                    }
+                    (a, b) => a.is_some().cmp(&b.is_some()),
+                }
+            }
+        };
+
+        if order.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+
+    rows
+}
+
+fn names(artifacts: &[games::Artifact], app: &App) -> Names {
+    let mut names = Names {
+        sets: BTreeMap::new(),
+        stats: BTreeMap::new(),
+    };
+
+    for artifact in artifacts {
+        names
+            .sets
+            .entry(artifact.setKey)
+            .or_insert_with(|| set_name(artifact.setKey, app));
+
+        let stats =
+            std::iter::once(artifact.mainStatKey).chain(artifact.substats.iter().map(|s| s.key));
+        for key in stats {
+            names
+                .stats
+                .entry(key)
+                .or_insert_with(|| stat_name(key, app));
+        }
+    }
+
+    names
+}
+
+fn search_text(artifact: &games::Artifact, names: &Names) -> String {
+    let mut text = vec![
+        names.sets[&artifact.setKey].clone(),
+        artifact.setKey.to_string(),
+        artifact.slotKey.to_string(),
+        names.stats[&artifact.mainStatKey].clone(),
+        artifact.mainStatKey.to_string(),
+    ];
+    for substat in &artifact.substats {
+        text.push(substat_label(substat, &names.stats[&substat.key]));
+        text.push(substat.key.to_string());
+    }
+
+    text.join(" ").to_lowercase()
+}
+
+pub fn set_name(key: games::SetKey, app: &App) -> String {
+    match &app.tables {
+        Some(tables) => tables.set_name(app.language, key),
+        None => key.to_string(),
+    }
+}
+
+pub fn stat_name(key: games::StatKey, app: &App) -> String {
+    match &app.tables {
+        Some(tables) => tables.stat_name(app.language, key),
+        None => key.to_string(),
+    }
+}
+
+fn substat_text(substat: &games::Substat, app: &App) -> String {
+    substat_label(substat, &stat_name(substat.key, app))
+}
+
+fn substat_label(substat: &games::Substat, name: &str) -> String {
+    let unit = if substat.key.is_percent() { "%" } else { "" };
+    format!("{name} {}{unit}", substat.value)
+}
+
+fn copy(artifacts: &[games::Artifact], export_invalid: bool, app: &App) {
+    let invalid = match export::check(artifacts, export_invalid) {
+        Ok(invalid) => invalid,
+        Err(e) => {
             app.message_tx
-                .send(Message::GoTo(State::Error(e.to_string())))
+                .send(Message::Toast(egui_notify::Toast::error(format!(
+                    "Not copied, {e}"
+                ))))
                 .unwrap();
+            return;
+        }
+    };
+
+    let json = serde_json::json!(games::Good::new(artifacts)).to_string();
+    if let Err(e) = arboard::Clipboard::new().and_then(|mut c| c.set_text(json.clone())) {
+        app.message_tx
+            .send(Message::GoTo(State::Error(e.to_string())))
+            .unwrap();
+    } else {
+        let toast = if invalid == 0 {
+            egui_notify::Toast::success("Copied")
         } else {
+            egui_notify::Toast::warning(format!("Copied, {invalid} artifacts failed validation"))
+        };
+        app.message_tx.send(Message::Toast(toast)).unwrap();
+        super::export::copied("artifacts", "json", json.as_bytes(), app);
+    }
+}
+
+// Keeps the characters, weapons and settings of an optimizer export and only
+// replaces its artifacts.
+fn merge(artifacts: &[games::Artifact], export_invalid: bool, app: &App) {
+    if let Err(e) = export::check(artifacts, export_invalid) {
+        app.message_tx
+            .send(Message::Toast(egui_notify::Toast::error(format!(
+                "Not merged, {e}"
+            ))))
+            .unwrap();
+        return;
+    }
+
+    let Some(original) = rfd::FileDialog::new()
+        .add_filter("GOOD", &["json"])
+        .pick_file()
+    else {
+        return;
+    };
+
+    let merged = std::fs::read(&original)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| Ok(serde_json::from_slice(&bytes)?))
+        .and_then(|database| games::merge::merge(database, artifacts));
+    let merged = match merged {
+        Ok(merged) => merged,
+        Err(e) => {
             app.message_tx
-                .send(Message::Toast(egui_notify::Toast::success("Copied")))
+                .send(Message::Toast(egui_notify::Toast::error(format!(
+                    "Error reading {}: {e}",
+                    original.display()
+                ))))
                 .unwrap();
+            return;
         }
+    };
+
+    let stem = original
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "good".to_string());
+    let mut dialog = rfd::FileDialog::new()
+        .set_file_name(format!("{stem}_merged.json"))
+        .add_filter("GOOD", &["json"]);
+    if let Some(dir) = original.parent() {
+        dialog = dialog.set_directory(dir);
     }
+    let Some(path) = dialog.save_file() else {
+        return;
+    };
+
+    let toast = match serde_json::to_string(&merged.database)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| Ok(std::fs::write(&path, json)?))
+    {
+        Ok(()) => {
+            super::export::run_hooks(path.clone(), "artifacts", app);
+            egui_notify::Toast::success(format!(
+                "Saved {}, {} of {} artifacts kept their character",
+                path.display(),
+                merged.located,
+                artifacts.len()
+            ))
+        }
+        Err(e) => egui_notify::Toast::error(format!("Error saving: {e}")),
+    };
+
+    app.message_tx.send(Message::Toast(toast)).unwrap();
 }
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use crate::{
+    app::{App, Message},
+    export::{self, Format, Sheet},
+    snapshots, tasks,
+};
+
+pub fn file_name(kind: &str, extension: &str, app: &App) -> String {
+    export::file_name(
+        app.game,
+        kind,
+        app.uids.get(app.game).as_deref(),
+        snapshots::now(),
+        extension,
+    )
+}
+
+// The payload is only built once a path was picked.
+pub fn save_as(
+    kind: &str,
+    extension: &str,
+    bytes: impl FnOnce() -> anyhow::Result<Vec<u8>>,
+    app: &App,
+) {
+    let mut dialog = rfd::FileDialog::new()
+        .set_file_name(file_name(kind, extension, app))
+        .add_filter(extension.to_uppercase(), &[extension]);
+    if let Some(dir) = &app.auto_save_dir {
+        dialog = dialog.set_directory(dir);
+    }
+    let Some(path) = dialog.save_file() else {
+        return;
+    };
+
+    match bytes().and_then(|bytes| Ok(std::fs::write(&path, bytes)?)) {
+        Ok(()) => {
+            app.message_tx
+                .send(Message::Toast(egui_notify::Toast::success(format!(
+                    "Saved {}",
+                    path.display()
+                ))))
+                .unwrap();
+            run_hooks(path, kind, app);
+        }
+        Err(e) => {
+            app.message_tx
+                .send(Message::Toast(egui_notify::Toast::error(format!(
+                    "Error saving: {e}"
+                ))))
+                .unwrap();
+        }
+    }
+}
+
+pub fn run_hooks(path: PathBuf, kind: &str, app: &App) {
+    tasks::run_hooks(
+        app.hooks.clone(),
+        path,
+        app.game,
+        kind.to_string(),
+        app.uids.get(app.game),
+        &app.message_tx,
+    );
+}
+
+// Copies have no file of their own, hooks get one in the temp dir.
+pub fn copied(kind: &str, extension: &str, bytes: &[u8], app: &App) {
+    if export::hooks::commands(&app.hooks).next().is_none() {
+        return;
+    }
+
+    let dir = std::env::temp_dir().join("stardb-exporter");
+    match export::auto_save(&dir, &file_name(kind, extension, app), bytes) {
+        Ok(path) => run_hooks(path, kind, app),
+        Err(e) => {
+            app.message_tx
+                .send(Message::Toast(egui_notify::Toast::error(format!(
+                    "Error writing the copy for hooks: {e}"
+                ))))
+                .unwrap();
+        }
+    }
+}
+
+// Spreadsheet exports, the sheet is only built when a button is clicked.
+pub fn buttons(ui: &mut egui::Ui, kind: &str, sheet: impl FnOnce() -> Sheet, app: &App) {
+    let mut format = None;
+
+    ui.horizontal(|ui| {
+        if ui.button("Export CSV...").clicked() {
+            format = Some(Format::Csv);
+        }
+        if ui.button("Export XLSX...").clicked() {
+            format = Some(Format::Xlsx);
+        }
+    });
+
+    if let Some(format) = format {
+        save_as(kind, format.extension(), || sheet().to_bytes(format), app);
+    }
+}
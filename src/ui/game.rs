@@ -2,14 +2,19 @@ use egui_remixicon::icons;
 
 use crate::{
     app::{App, Message, State},
-    games,
+    games, snapshots, tasks,
 };
 
 pub fn show(ui: &mut egui::Ui, app: &App) {
     match app.game {
         games::Game::Hsr => {
             if ui.button("Achievement Exporter").clicked() {
-                app.game.achievements(&app.message_tx);
+                tasks::achievements(
+                    app.game,
+                    app.language,
+                    app.uids.get(app.game),
+                    &app.message_tx,
+                );
                 app.message_tx
                     .send(Message::GoTo(State::Waiting("Preparing".to_string())))
                     .unwrap();
@@ -18,27 +23,76 @@ pub fn show(ui: &mut egui::Ui, app: &App) {
             if ui.button("Warp Exporter").clicked() {
                 app.message_tx.send(Message::GoTo(State::PullMenu)).unwrap();
             }
+
+            ui.separator();
+
+            last_capture(ui, app, snapshots::Kind::Achievements, "achievements");
+
+            ui.separator();
+
+            snapshot_history(ui, app);
         }
         games::Game::Gi => {
             ui.colored_label(ui.visuals().hyperlink_color, format!("{} Make sure, that you fresh started the game before using the achievement exporter!!", icons::INFORMATION_LINE));
 
             if ui.button("Achievement Exporter").clicked() {
-                app.game.achievements(&app.message_tx);
+                tasks::achievements(
+                    app.game,
+                    app.language,
+                    app.uids.get(app.game),
+                    &app.message_tx,
+                );
                 app.message_tx
                     .send(Message::GoTo(State::Waiting("Preparing".to_string())))
                     .unwrap();
             }
 
-            if ui.button("Artifact Exporter").clicked() {
-                app.game.artifacts(&app.message_tx);
-                app.message_tx
-                    .send(Message::GoTo(State::Waiting("Preparing".to_string())))
-                    .unwrap();
+            if let Some(tables) = &app.tables {
+                if ui.button("Artifact Exporter").clicked() {
+                    tasks::artifacts(
+                        app.game,
+                        tables.clone(),
+                        app.uids.get(app.game),
+                        &app.message_tx,
+                    );
+                    app.message_tx
+                        .send(Message::GoTo(State::Waiting("Preparing".to_string())))
+                        .unwrap();
+                }
+            } else {
+                ui.add_enabled(false, egui::Button::new("Artifact Exporter"));
             }
 
             if ui.button("Wish Exporter").clicked() {
                 app.message_tx.send(Message::GoTo(State::PullMenu)).unwrap();
             }
+
+            ui.separator();
+
+            last_capture(ui, app, snapshots::Kind::Achievements, "achievements");
+            last_capture(ui, app, snapshots::Kind::Artifacts, "artifacts");
+
+            ui.separator();
+
+            snapshot_history(ui, app);
+
+            ui.separator();
+
+            ui.label(format!(
+                "Artifact data: {}",
+                app.tables
+                    .as_ref()
+                    .map(|t| t.describe())
+                    .unwrap_or("Loading".to_string())
+            ));
+
+            if ui
+                .button("Import excel dumps (folder with ExcelBinOutput and TextMap)")
+                .clicked()
+                && let Some(dir) = rfd::FileDialog::new().pick_folder()
+            {
+                tasks::import_tables(dir, &app.message_tx);
+            }
         }
         games::Game::Zzz => {
             if ui.button("Signal Exporter").clicked() {
@@ -47,3 +101,76 @@ pub fn show(ui: &mut egui::Ui, app: &App) {
         }
     }
 }
+
+// Rebuilds a result screen from the cached capture, without the game running.
+fn last_capture(ui: &mut egui::Ui, app: &App, kind: snapshots::Kind, name: &str) {
+    if ui
+        .button(format!("Re-export {name} from last capture"))
+        .clicked()
+    {
+        app.message_tx
+            .send(Message::GoTo(State::Waiting("Loading capture".to_string())))
+            .unwrap();
+        tasks::restore_capture(app.game, kind, &app.message_tx);
+    }
+}
+
+fn snapshot_history(ui: &mut egui::Ui, app: &App) {
+    let uid = app.uids.get(app.game);
+
+    ui.horizontal(|ui| {
+        ui.label("UID:");
+
+        let mut edit = uid.clone().unwrap_or_default();
+        if ui.text_edit_singleline(&mut edit).changed() {
+            app.message_tx.send(Message::Uid(app.game, edit)).unwrap();
+        }
+    });
+
+    match uid {
+        Some(uid) => {
+            if !app.accounts.users.is_empty() || app.accounts.binding(app.game, &uid).is_some() {
+                account_binding(ui, &uid, app);
+            }
+
+            if ui.button("Changes since last snapshot").clicked() {
+                app.message_tx
+                    .send(Message::GoTo(State::Waiting(
+                        "Loading snapshots".to_string(),
+                    )))
+                    .unwrap();
+                tasks::snapshot_report(app.game, uid, app.language, &app.message_tx);
+            }
+        }
+        None => {
+            ui.label("Set your UID to keep a snapshot of every capture.");
+        }
+    }
+}
+
+// Which stardb account the UID syncs to.
+fn account_binding(ui: &mut egui::Ui, uid: &str, app: &App) {
+    let bound = app.accounts.binding(app.game, uid).map(str::to_string);
+
+    ui.horizontal(|ui| {
+        ui.label("Account:");
+
+        let mut selected = bound.clone();
+        ui.selectable_value(&mut selected, None, "Active account");
+        for user in &app.accounts.users {
+            ui.selectable_value(&mut selected, Some(user.username.clone()), &user.username);
+        }
+        // Syncs of the UID wait until it logs back in.
+        if let Some(username) = app.accounts.logged_out(app.game, Some(uid)) {
+            ui.selectable_value(
+                &mut selected,
+                bound.clone(),
+                format!("{username} (logged out)"),
+            );
+        }
+
+        if selected != bound {
+            app.message_tx.send(Message::Bind(selected)).unwrap();
+        }
+    });
+}
@@ -2,7 +2,7 @@ use egui_remixicon::icons;
 
 use crate::{
     app::{App, Message, State},
-    games, themes,
+    data, games, themes,
 };
 
 pub fn show(ctx: &egui::Context, ui: &mut egui::Ui, app: &mut App) {
@@ -14,7 +14,7 @@ pub fn show(ctx: &egui::Context, ui: &mut egui::Ui, app: &mut App) {
         let waiting = matches!(app.state, State::Waiting(_));
 
         let heading_text = match app.state {
-            State::Game | State::Achievements(_) | State::PullMenu => match app.game {
+            State::Game | State::Achievements(..) | State::PullMenu | State::Snapshots(_) => match app.game {
                 games::Game::Hsr => "Honkai Star Rail",
                 games::Game::Gi => "Genshin Impact",
                 games::Game::Zzz => "Zenless Zone Zero",
@@ -40,7 +40,7 @@ pub fn show(ctx: &egui::Context, ui: &mut egui::Ui, app: &mut App) {
         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
             ui.add_space(32.0);
 
-            let height = if let Some(user) = &app.user {
+            let height = if let Some(user) = app.accounts.active() {
                 let mut icon_format = egui::TextFormat::simple(
                     egui::FontId::proportional(20.0),
                     ui.visuals().text_color(),
@@ -91,7 +91,7 @@ pub fn show(ctx: &egui::Context, ui: &mut egui::Ui, app: &mut App) {
 
                         if ui.button(website_job).clicked() {
                             let url = match app.state {
-                                State::Achievements(_) => app.game.achievement_url(),
+                                State::Achievements(..) => app.game.achievement_url(),
                                 State::PullMenu | State::Pulls(_) => app.game.pull_url(),
                                 _ => "https://stardb.gg".to_string(),
                             };
@@ -108,6 +108,34 @@ pub fn show(ctx: &egui::Context, ui: &mut egui::Ui, app: &mut App) {
                         if ui.button(logout_job).clicked() {
                             app.message_tx.send(Message::Logout).unwrap();
                         }
+
+                        ui.separator();
+
+                        for other in &app.accounts.users {
+                            if other.username == user.username {
+                                continue;
+                            }
+
+                            let mut switch_job = egui::text::LayoutJob::default();
+                            switch_job.append(icons::ACCOUNT_CIRCLE_LINE, 0.0, icon_format.clone());
+                            switch_job.append(&other.username, 8.0, text_format.clone());
+
+                            if ui.button(switch_job).clicked() {
+                                app.message_tx
+                                    .send(Message::SwitchAccount(other.username.clone()))
+                                    .unwrap();
+                            }
+                        }
+
+                        let mut add_job = egui::text::LayoutJob::default();
+                        add_job.append(icons::USER_ADD_LINE, 0.0, icon_format.clone());
+                        add_job.append("Add account", 8.0, text_format.clone());
+
+                        if ui.button(add_job).clicked() {
+                            app.message_tx
+                                .send(Message::GoTo(State::Login(String::new(), String::new())))
+                                .unwrap();
+                        }
                     },
                 );
 
@@ -151,6 +179,18 @@ pub fn show(ctx: &egui::Context, ui: &mut egui::Ui, app: &mut App) {
                 .inner
             };
 
+            if app.pending_syncs > 0 {
+                ui.label(
+                    egui::RichText::new(format!("{} {}", icons::CLOUD_OFF_LINE, app.pending_syncs))
+                        .size(16.0),
+                )
+                .on_hover_text(format!(
+                    "{} stardb syncs waiting to be retried",
+                    app.pending_syncs
+                ));
+                ui.add_space(8.0);
+            }
+
             let old_button_padding = ui.style().spacing.button_padding;
             ui.style_mut().spacing.button_padding = egui::vec2(0.0, 0.0);
             let button = egui::Button::new(
@@ -204,6 +244,35 @@ pub fn show(ctx: &egui::Context, ui: &mut egui::Ui, app: &mut App) {
                 },
             );
 
+            let button = egui::Button::new(
+                egui::RichText::new(egui_remixicon::icons::TRANSLATE_2).size(20.0),
+            );
+            let button = button.min_size(egui::vec2(48.0, height));
+
+            let language_button = ui.add(button);
+
+            if language_button.clicked() {
+                app.language_popup_open = true;
+            }
+
+            egui::Popup::from_response(&language_button)
+                .open_bool(&mut app.language_popup_open)
+                .show(|ui| {
+                    ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
+                    ui.visuals_mut().widgets.inactive.bg_stroke.color =
+                        ui.visuals().widgets.active.bg_stroke.color;
+
+                    let mut language = app.language;
+
+                    for l in data::Language::ALL {
+                        ui.selectable_value(&mut language, l, l.name());
+                    }
+
+                    if language != app.language {
+                        app.message_tx.send(Message::Language(language)).unwrap();
+                    }
+                });
+
             ui.style_mut().spacing.button_padding = old_button_padding;
             let text = egui::Color32::BLACK;
             let accent = egui::Color32::from_rgb(250, 204, 21);
@@ -1,6 +1,9 @@
 use std::{sync::mpsc, thread};
 
-use crate::app::{App, Message, State, User};
+use crate::{
+    app::{App, Message, State, User},
+    stardb,
+};
 
 pub fn show(ui: &mut egui::Ui, username: &str, password: &str, app: &App) {
     let mut username = username.to_string();
@@ -31,35 +34,28 @@ fn login(username: &str, password: &str, message_tx: &mpsc::Sender<Message>) {
     let message_tx = message_tx.clone();
 
     thread::spawn(move || {
-        let json = serde_json::json!({
-            "username": username,
-            "password": password
-        });
-
-        let id = ureq::post("https://stardb.gg/api/users/auth/login")
-            .send_json(json)
-            .ok()
-            .and_then(|r| {
-                r.headers()
-                    .get("Set-Cookie")
-                    .and_then(|h| h.to_str().ok())
-                    .and_then(|id| id.split(';').next())
-                    .map(|s| s.to_string())
-            });
-
-        if let Some(id) = id {
-            let username = username.to_string();
-
-            let user = User { id, username };
-
-            message_tx.send(Message::User(Some(user))).unwrap();
-            message_tx.send(Message::GoTo(State::Menu)).unwrap();
-        } else {
-            message_tx
-                .send(Message::GoTo(State::Error(
-                    "There was an error during the login".to_string(),
-                )))
-                .unwrap();
+        let login = stardb::Login {
+            username: &username,
+            password: &password,
+        };
+
+        match stardb::Client::default().login(&login) {
+            Ok(session) => {
+                let user = User {
+                    id: session.cookie,
+                    username: session.username,
+                };
+
+                message_tx.send(Message::User(user)).unwrap();
+                message_tx.send(Message::GoTo(State::Menu)).unwrap();
+            }
+            Err(e) => {
+                message_tx
+                    .send(Message::GoTo(State::Error(format!(
+                        "There was an error during the login: {e}"
+                    ))))
+                    .unwrap();
+            }
         }
     });
 }
@@ -4,6 +4,11 @@ use crate::{
 };
 
 pub fn show(ui: &mut egui::Ui, app: &App) {
+    if app.session_locked {
+        unlock(ui, app);
+        ui.separator();
+    }
+
     if ui.button("Honkai: Star Rail").clicked() {
         app.message_tx.send(Message::Game(Game::Hsr)).unwrap();
     }
@@ -15,4 +20,156 @@ pub fn show(ui: &mut egui::Ui, app: &App) {
     if ui.button("Zenless Zone Zero").clicked() {
         app.message_tx.send(Message::Game(Game::Zzz)).unwrap();
     }
+
+    ui.separator();
+
+    match &app.auto_save_dir {
+        Some(dir) => {
+            ui.label(format!("Auto-saving results to {}", dir.display()));
+        }
+        None => {
+            ui.label("Auto-save is off");
+        }
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button("Choose auto-save folder...").clicked()
+            && let Some(dir) = rfd::FileDialog::new().pick_folder()
+        {
+            app.message_tx
+                .send(Message::AutoSaveDir(Some(dir)))
+                .unwrap();
+        }
+
+        if app.auto_save_dir.is_some() && ui.button("Disable auto-save").clicked() {
+            app.message_tx.send(Message::AutoSaveDir(None)).unwrap();
+        }
+    });
+
+    ui.separator();
+
+    let mut enabled = app.server.is_some();
+    if ui
+        .checkbox(
+            &mut enabled,
+            format!(
+                "Serve captures on http://127.0.0.1:{}",
+                app.server
+                    .as_ref()
+                    .map_or(crate::server::DEFAULT_PORT, |s| s.port())
+            ),
+        )
+        .changed()
+    {
+        app.message_tx.send(Message::Server(enabled)).unwrap();
+    }
+
+    ui.separator();
+
+    ui.collapsing("Post-export hooks", |ui| {
+        ui.label(
+            "Commands to run after every export, one per line. {file}, {game}, {kind} and \
+             {uid} are replaced, and also set as STARDB_FILE, STARDB_GAME, STARDB_KIND and \
+             STARDB_UID. Copies to the clipboard get a file in the temp dir. Hooks still \
+             running after a minute are stopped.",
+        );
+
+        let mut hooks = app.hooks.clone();
+        if ui
+            .add(
+                egui::TextEdit::multiline(&mut hooks)
+                    .hint_text("upload.sh {file} {game} {kind} {uid}"),
+            )
+            .changed()
+        {
+            app.message_tx.send(Message::Hooks(hooks)).unwrap();
+        }
+    });
+
+    ui.separator();
+
+    ui.collapsing("Webhook", |ui| {
+        ui.label(
+            "Every completed export is POSTed to this URL. In the payload template, {game}, \
+             {kind}, {uid}, {timestamp} and {data} are replaced by JSON values.",
+        );
+
+        let mut webhook = app.webhook.clone();
+
+        ui.add(egui::TextEdit::singleline(&mut webhook.url).hint_text("https://example.com/hook"));
+        ui.add(
+            egui::TextEdit::multiline(&mut webhook.headers).hint_text("Authorization: Bearer ..."),
+        );
+        ui.add(
+            egui::TextEdit::multiline(&mut webhook.template)
+                .hint_text(crate::webhook::DEFAULT_TEMPLATE),
+        );
+
+        if webhook != app.webhook {
+            app.message_tx.send(Message::Webhook(webhook)).unwrap();
+        }
+    });
+
+    ui.separator();
+
+    ui.collapsing("Privacy", |ui| {
+        ui.label(
+            "Your stardb session is stored encrypted. Set a passphrase to be asked for it on \
+             launch, instead of using the key file next to it.",
+        );
+
+        let id = ui.id().with("passphrase");
+        let mut passphrase: String = ui.data_mut(|d| d.get_temp(id)).unwrap_or_default();
+        ui.add(
+            egui::TextEdit::singleline(&mut passphrase)
+                .password(true)
+                .hint_text("Passphrase"),
+        );
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!passphrase.is_empty(), egui::Button::new("Set passphrase"))
+                .clicked()
+            {
+                app.message_tx
+                    .send(Message::Passphrase(Some(std::mem::take(&mut passphrase))))
+                    .unwrap();
+            }
+
+            if app.passphrase.is_some() && ui.button("Remove passphrase").clicked() {
+                app.message_tx.send(Message::Passphrase(None)).unwrap();
+            }
+        });
+        ui.data_mut(|d| d.insert_temp(id, passphrase));
+
+        ui.add_space(8.0);
+
+        if ui
+            .button("Forget everything")
+            .on_hover_text("Logs out and deletes the saved session, pull urls and snapshots")
+            .clicked()
+        {
+            app.message_tx.send(Message::ForgetEverything).unwrap();
+        }
+    });
+}
+
+fn unlock(ui: &mut egui::Ui, app: &App) {
+    ui.label("Your stardb session is protected by a passphrase");
+
+    let id = ui.id().with("unlock");
+    let mut passphrase: String = ui.data_mut(|d| d.get_temp(id)).unwrap_or_default();
+
+    ui.horizontal(|ui| {
+        let edit = ui.add(egui::TextEdit::singleline(&mut passphrase).password(true));
+        let enter = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+        if ui.button("Unlock").clicked() || enter {
+            app.message_tx
+                .send(Message::Unlock(std::mem::take(&mut passphrase)))
+                .unwrap();
+        }
+    });
+
+    ui.data_mut(|d| d.insert_temp(id, passphrase));
 }
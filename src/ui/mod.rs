@@ -1,10 +1,13 @@
 pub mod achievements;
+pub mod analytics;
 pub mod artifacts;
 pub mod error;
+pub mod export;
 pub mod game;
 pub mod header;
 pub mod login;
 pub mod menu;
 pub mod pull_menu;
 pub mod pulls;
+pub mod snapshots;
 pub mod waiting;
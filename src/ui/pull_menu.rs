@@ -1,8 +1,6 @@
-use std::thread;
-
 use crate::{
     app::{App, Message, State},
-    games,
+    games, tasks,
 };
 
 pub fn show(ui: &mut egui::Ui, app: &App) {
@@ -63,16 +61,7 @@ pub fn show(ui: &mut egui::Ui, app: &App) {
         games::Game::Zzz => &app.paths.zzz,
     } {
         if ui.button("Get Url").clicked() {
-            let message_tx = app.message_tx.clone();
-            let path = path.clone();
-
-            thread::spawn(move || {
-                match games::pulls_from_game_path(&path) {
-                    Ok(url) => message_tx.send(Message::GoTo(State::Pulls(url))),
-                    Err(e) => message_tx.send(Message::GoTo(State::Error(e.to_string()))),
-                }
-                .unwrap()
-            });
+            tasks::pulls(app.game, path.clone(), &app.message_tx);
 
             app.message_tx
                 .send(Message::GoTo(State::Waiting("Running".to_string())))
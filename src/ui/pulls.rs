@@ -1,6 +1,8 @@
+use std::thread;
+
 use crate::{
     app::{App, Message, State},
-    games,
+    games, stardb, sync_queue, tasks,
 };
 
 pub fn show(ui: &mut egui::Ui, url: &str, app: &App) {
@@ -15,9 +17,14 @@ pub fn show(ui: &mut egui::Ui, url: &str, app: &App) {
             app.message_tx
                 .send(Message::Toast(egui_notify::Toast::success("Copied")))
                 .unwrap();
+            super::export::copied("pulls", "txt", url.as_bytes(), app);
         }
     }
 
+    if ui.button("Save as...").clicked() {
+        super::export::save_as("pulls", "txt", || Ok(url.as_bytes().to_vec()), app);
+    }
+
     let import_url = match app.game {
         games::Game::Hsr => "https://stardb.gg/warp-import",
         games::Game::Gi => "https://stardb.gg/genshin/wish-import",
@@ -26,38 +33,53 @@ pub fn show(ui: &mut egui::Ui, url: &str, app: &App) {
 
     ui.hyperlink_to("Click here to import", import_url);
 
-    if ui.button("Sync to stardb").clicked() {
-        let import_url = match app.game {
-            games::Game::Hsr => "https://stardb.gg/api/warps-import",
-            games::Game::Gi => "https://stardb.gg/api/gi/wishes-import",
-            games::Game::Zzz => "https://stardb.gg/api/zzz/signals-import",
-        };
+    // Not imported without an account either, the UID belongs to that one.
+    let uid = app.uids.get(app.game);
+    if let Some(username) = app.accounts.logged_out(app.game, uid.as_deref()) {
+        ui.label(format!("Log in as \"{username}\" to sync this UID"));
+        return;
+    }
 
-        let request = if let Some(user) = &app.user {
-            ureq::post(import_url).header("Cookie", &user.id)
-        } else {
-            ureq::post(import_url)
-        };
-
-        match request.send_json(serde_json::json!({"url": url})) {
-            Ok(mut r) => {
-                app.message_tx
-                    .send(Message::Toast(egui_notify::Toast::success(format!(
-                        "Synced uid {}",
-                        r.body_mut().read_json::<serde_json::Value>().unwrap()["uid"]
-                    ))))
-                    .unwrap();
-                app.message_tx
-                    .send(Message::Toast(egui_notify::Toast::success("Copied")))
-                    .unwrap();
-            }
-            Err(e) => {
-                app.message_tx
-                    .send(Message::Toast(egui_notify::Toast::error(format!(
-                        "Error: {e}"
-                    ))))
-                    .unwrap();
-            }
-        }
+    let user = app.accounts.for_uid(app.game, uid.as_deref());
+    let label = match user {
+        Some(user) => format!("Sync to stardb: \"{}\"", user.username),
+        None => "Sync to stardb".to_string(),
+    };
+
+    if ui.button(label).clicked() {
+        let message_tx = app.message_tx.clone();
+        let game = app.game;
+        let user = user.cloned();
+        let cookie = user.as_ref().map(|user| user.id.clone());
+        let url = url.to_string();
+
+        thread::spawn(move || {
+            let client = stardb::Client::default();
+            let toast = match client.import_pulls(cookie.as_deref(), game, &url) {
+                Ok(import) => {
+                    if let Some(user) = user {
+                        message_tx
+                            .send(Message::DetectedUid(
+                                game,
+                                import.uid.to_string(),
+                                Some(user.username),
+                            ))
+                            .unwrap();
+                    }
+                    egui_notify::Toast::success(format!("Synced uid {}", import.uid))
+                }
+                Err(stardb::Error::Network(_)) => {
+                    let job = sync_queue::Job::Pulls {
+                        base_url: client.base_url().to_string(),
+                        game,
+                        username: user.map(|user| user.username),
+                        url,
+                    };
+                    return tasks::queue_sync(job, &message_tx);
+                }
+                Err(e) => egui_notify::Toast::error(format!("Error: {e}")),
+            };
+            message_tx.send(Message::Toast(toast)).unwrap();
+        });
     }
 }
@@ -0,0 +1,92 @@
+use crate::{app::App, export, games, snapshots::Report};
+
+use super::artifacts::{set_name, stat_name};
+
+pub fn show(ui: &mut egui::Ui, report: &Report, app: &App) {
+    ui.label(format!("UID {}", report.uid));
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.heading("Artifacts");
+        match &report.artifacts {
+            Some(artifacts) => {
+                ui.label(format!(
+                    "{} to {}",
+                    export::format_timestamp(artifacts.previous),
+                    export::format_timestamp(artifacts.current)
+                ));
+
+                let diff = &artifacts.diff;
+
+                ui.collapsing(format!("New ({})", diff.new.len()), |ui| {
+                    for artifact in &diff.new {
+                        ui.label(describe(artifact, app));
+                    }
+                });
+
+                ui.collapsing(format!("Removed or fed ({})", diff.removed.len()), |ui| {
+                    for artifact in &diff.removed {
+                        ui.label(describe(artifact, app));
+                    }
+                });
+
+                ui.collapsing(format!("Upgraded ({})", diff.upgraded.len()), |ui| {
+                    for upgrade in &diff.upgraded {
+                        let gained: Vec<_> = upgrade
+                            .gained
+                            .iter()
+                            .map(|s| format!("+{} {}", s.value, stat_name(s.key, app)))
+                            .collect();
+
+                        ui.label(format!(
+                            "{} +{} → +{}: {}",
+                            describe(&upgrade.after, app),
+                            upgrade.before.level,
+                            upgrade.after.level,
+                            gained.join(", ")
+                        ));
+                    }
+                });
+            }
+            None => {
+                ui.label("Needs at least two artifact snapshots.");
+            }
+        }
+
+        ui.separator();
+
+        ui.heading("Achievements");
+        match &report.achievements {
+            Some(achievements) => {
+                ui.label(format!(
+                    "{} to {}",
+                    export::format_timestamp(achievements.previous),
+                    export::format_timestamp(achievements.current)
+                ));
+
+                ui.collapsing(
+                    format!("Newly completed ({})", achievements.completed.len()),
+                    |ui| {
+                        for id in &achievements.completed {
+                            match achievements.names.get(id) {
+                                Some(name) => ui.label(format!("{name} ({id})")),
+                                None => ui.label(id.to_string()),
+                            };
+                        }
+                    },
+                );
+            }
+            None => {
+                ui.label("Needs at least two achievement snapshots.");
+            }
+        }
+    });
+}
+
+fn describe(artifact: &games::Artifact, app: &App) -> String {
+    format!(
+        "{} {} ({})",
+        set_name(artifact.setKey, app),
+        artifact.slotKey,
+        stat_name(artifact.mainStatKey, app)
+    )
+}
@@ -0,0 +1,228 @@
+// Encrypted storage for the stardb session, so that cookies don't sit in the
+// plaintext settings file. The key is derived from a passphrase when the user
+// sets one, and is otherwise a random key file created on first use.
+
+use std::path::{Path, PathBuf};
+
+use base64::prelude::*;
+use chacha20poly1305::{
+    ChaCha20Poly1305, Key, Nonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng, rand_core::RngCore},
+};
+
+const KEY_FILE: &str = "session.key";
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Sealed {
+    passphrase: bool,
+    salt: String,
+    nonce: String,
+    data: String,
+}
+
+pub struct Vault {
+    dir: PathBuf,
+    file: String,
+}
+
+impl Vault {
+    pub fn new(dir: &Path) -> Self {
+        Self::named(dir, "session")
+    }
+
+    // Another file in `dir`, sharing the key file.
+    pub fn named(dir: &Path, name: &str) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            file: format!("{name}.vault"),
+        }
+    }
+
+    // Whether `load` needs the passphrase.
+    pub fn is_locked(&self) -> bool {
+        self.sealed()
+            .ok()
+            .flatten()
+            .is_some_and(|sealed| sealed.passphrase)
+    }
+
+    pub fn save<T: serde::Serialize>(
+        &self,
+        value: &T,
+        passphrase: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let mut salt = [0; 16];
+        OsRng.fill_bytes(&mut salt);
+
+        let cipher = ChaCha20Poly1305::new(&self.key(passphrase, &salt)?);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let data = cipher
+            .encrypt(&nonce, serde_json::to_vec(value)?.as_slice())
+            .map_err(|_| anyhow::anyhow!("Couldn't encrypt the session"))?;
+
+        let sealed = Sealed {
+            passphrase: passphrase.is_some(),
+            salt: BASE64_STANDARD.encode(salt),
+            nonce: BASE64_STANDARD.encode(nonce),
+            data: BASE64_STANDARD.encode(data),
+        };
+
+        std::fs::create_dir_all(&self.dir)?;
+        write_private(&self.dir.join(&self.file), &serde_json::to_vec(&sealed)?)?;
+
+        Ok(())
+    }
+
+    // `None` if nothing was saved yet.
+    pub fn load<T: serde::de::DeserializeOwned>(
+        &self,
+        passphrase: Option<&str>,
+    ) -> anyhow::Result<Option<T>> {
+        let Some(sealed) = self.sealed()? else {
+            return Ok(None);
+        };
+
+        if sealed.passphrase && passphrase.is_none() {
+            return Err(anyhow::anyhow!("The session is protected by a passphrase"));
+        }
+        let passphrase = passphrase.filter(|_| sealed.passphrase);
+
+        let salt = BASE64_STANDARD.decode(&sealed.salt)?;
+        let nonce = BASE64_STANDARD.decode(&sealed.nonce)?;
+        if nonce.len() != 12 {
+            return Err(anyhow::anyhow!("Corrupted session file"));
+        }
+
+        let cipher = ChaCha20Poly1305::new(&self.key(passphrase, &salt)?);
+        let data = cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                BASE64_STANDARD.decode(&sealed.data)?.as_slice(),
+            )
+            .map_err(|_| match passphrase {
+                Some(_) => anyhow::anyhow!("Wrong passphrase"),
+                None => anyhow::anyhow!("Couldn't decrypt the session"),
+            })?;
+
+        Ok(Some(serde_json::from_slice(&data)?))
+    }
+
+    // Deletes the file and the key, which makes the other vaults in the same
+    // dir unreadable too.
+    pub fn forget(&self) -> anyhow::Result<()> {
+        for file in [self.file.as_str(), KEY_FILE] {
+            let path = self.dir.join(file);
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn sealed(&self) -> anyhow::Result<Option<Sealed>> {
+        let path = self.dir.join(&self.file);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_slice(&std::fs::read(path)?)?))
+    }
+
+    fn key(&self, passphrase: Option<&str>, salt: &[u8]) -> anyhow::Result<Key> {
+        let mut key = Key::default();
+
+        match passphrase {
+            Some(passphrase) => argon2::Argon2::default()
+                .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                .map_err(|e| anyhow::anyhow!("Couldn't derive the key: {e}"))?,
+            None => {
+                let path = self.dir.join(KEY_FILE);
+                if !path.exists() {
+                    let mut bytes = [0; 32];
+                    OsRng.fill_bytes(&mut bytes);
+                    std::fs::create_dir_all(&self.dir)?;
+                    write_private(&path, &bytes)?;
+                }
+
+                let bytes = std::fs::read(&path)?;
+                if bytes.len() != key.len() {
+                    return Err(anyhow::anyhow!("Corrupted key file {}", path.display()));
+                }
+                key.copy_from_slice(&bytes);
+            }
+        }
+
+        Ok(key)
+    }
+}
+
+// Only readable by the current user where the platform allows it.
+fn write_private(path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::{io::Write, os::unix::fs::OpenOptionsExt};
+
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?
+            .write_all(bytes)?;
+    }
+    #[cfg(not(unix))]
+    std::fs::write(path, bytes)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault(name: &str) -> Vault {
+        let dir = std::env::temp_dir().join(format!("stardb-vault-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        Vault::new(&dir)
+    }
+
+    #[test]
+    fn round_trips_with_the_key_file() {
+        let vault = vault("key-file");
+        assert_eq!(vault.load::<String>(None).unwrap(), None);
+
+        vault.save(&"id=secret", None).unwrap();
+        assert!(!vault.is_locked());
+        assert_eq!(
+            vault.load::<String>(None).unwrap().as_deref(),
+            Some("id=secret")
+        );
+
+        let file = std::fs::read_to_string(vault.dir.join("session.vault")).unwrap();
+        assert!(!file.contains("secret"));
+
+        vault.forget().unwrap();
+        assert!(!vault.dir.join("session.vault").exists());
+        assert!(!vault.dir.join(KEY_FILE).exists());
+        std::fs::remove_dir_all(&vault.dir).unwrap();
+    }
+
+    #[test]
+    fn needs_the_right_passphrase() {
+        let vault = vault("passphrase");
+
+        vault.save(&"id=secret", Some("hunter2")).unwrap();
+        assert!(vault.is_locked());
+        assert!(!vault.dir.join(KEY_FILE).exists());
+
+        assert!(vault.load::<String>(None).is_err());
+        assert!(vault.load::<String>(Some("wrong")).is_err());
+        assert_eq!(
+            vault.load::<String>(Some("hunter2")).unwrap().as_deref(),
+            Some("id=secret")
+        );
+
+        std::fs::remove_dir_all(&vault.dir).unwrap();
+    }
+}
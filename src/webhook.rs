@@ -0,0 +1,241 @@
+// Optional HTTP target that gets every completed export POSTed to it, for
+// self-hosted dashboards next to the stardb sync.
+//
+// The payload template is JSON with placeholders that are replaced by JSON
+// values: {game}, {kind} and {uid} as strings (uid may be null), {timestamp}
+// as a number and {data} as the export itself (GOOD, stardb achievements or
+// the pull url).
+
+use std::{thread, time::Duration};
+
+use ureq::Agent;
+
+use crate::games::Game;
+
+pub const DEFAULT_TEMPLATE: &str =
+    r#"{"game": {game}, "kind": {kind}, "uid": {uid}, "timestamp": {timestamp}, "data": {data}}"#;
+
+const ATTEMPTS: u32 = 3;
+// Per attempt, so that a hanging endpoint can't hold the export thread.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Webhook {
+    pub url: String,
+    // `Name: value`, one per line.
+    pub headers: String,
+    // Empty for `DEFAULT_TEMPLATE`.
+    pub template: String,
+}
+
+impl Webhook {
+    pub fn is_enabled(&self) -> bool {
+        !self.url.trim().is_empty()
+    }
+
+    pub fn payload(
+        &self,
+        game: Game,
+        kind: &str,
+        uid: Option<&str>,
+        timestamp: u64,
+        data: &serde_json::Value,
+    ) -> anyhow::Result<String> {
+        let template = match self.template.trim() {
+            "" => DEFAULT_TEMPLATE,
+            template => template,
+        };
+
+        let payload = template
+            .replace("{game}", &serde_json::to_string(game.id())?)
+            .replace("{kind}", &serde_json::to_string(kind)?)
+            .replace("{uid}", &serde_json::to_string(&uid)?)
+            .replace("{timestamp}", &timestamp.to_string())
+            .replace("{data}", &serde_json::to_string(data)?);
+
+        if let Err(e) = serde_json::from_str::<serde_json::Value>(&payload) {
+            return Err(anyhow::anyhow!("Webhook template isn't valid JSON: {e}"));
+        }
+
+        Ok(payload)
+    }
+
+    // Retries server errors and failed connections, waiting longer each time.
+    pub fn send(&self, payload: &str) -> anyhow::Result<()> {
+        self.send_with_backoff(payload, Duration::from_secs(1))
+    }
+
+    fn send_with_backoff(&self, payload: &str, backoff: Duration) -> anyhow::Result<()> {
+        let headers = self.parse_headers()?;
+        let agent: Agent = Agent::config_builder()
+            .timeout_global(Some(TIMEOUT))
+            .build()
+            .into();
+
+        let mut attempt = 1;
+        loop {
+            let mut request = agent
+                .post(self.url.trim())
+                .header("Content-Type", "application/json");
+            for (name, value) in &headers {
+                request = request.header(*name, *value);
+            }
+
+            let e = match request.send(payload) {
+                Ok(_) => return Ok(()),
+                Err(e) => e,
+            };
+
+            let retry = match &e {
+                ureq::Error::StatusCode(status) => *status >= 500 || *status == 429,
+                ureq::Error::Io(_) | ureq::Error::ConnectionFailed | ureq::Error::Timeout(_) => {
+                    true
+                }
+                _ => false,
+            };
+            if !retry || attempt == ATTEMPTS {
+                return Err(anyhow::anyhow!(
+                    "Webhook failed after {attempt} attempts: {e}"
+                ));
+            }
+
+            tracing::warn!("Webhook attempt {attempt} failed: {e}");
+            thread::sleep(backoff * 2u32.pow(attempt - 1));
+            attempt += 1;
+        }
+    }
+
+    fn parse_headers(&self) -> anyhow::Result<Vec<(&str, &str)>> {
+        self.headers
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(|l| {
+                l.split_once(':')
+                    .map(|(name, value)| (name.trim(), value.trim()))
+                    .filter(|(name, _)| !name.is_empty())
+                    .ok_or_else(|| anyhow::anyhow!("Invalid webhook header: {l}"))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::mpsc,
+    };
+
+    use super::*;
+
+    #[test]
+    fn fills_in_the_template() {
+        let data = serde_json::json!({ "hsr_achievements": [1, 2] });
+
+        let webhook = Webhook::default();
+        assert_eq!(
+            webhook
+                .payload(Game::Hsr, "achievements", Some("100000000"), 10, &data)
+                .unwrap(),
+            r#"{"game": "hsr", "kind": "achievements", "uid": "100000000", "timestamp": 10, "data": {"hsr_achievements":[1,2]}}"#
+        );
+
+        let webhook = Webhook {
+            template: r#"{"text": {kind}, "uid": {uid}}"#.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            webhook
+                .payload(Game::Hsr, "pulls", None, 10, &data)
+                .unwrap(),
+            r#"{"text": "pulls", "uid": null}"#
+        );
+
+        let webhook = Webhook {
+            template: r#"{"text": "{kind}"}"#.to_string(),
+            ..Default::default()
+        };
+        assert!(
+            webhook
+                .payload(Game::Hsr, "pulls", None, 10, &data)
+                .is_err()
+        );
+    }
+
+    // A stand-in that answers with `statuses` in order and hands back what it got.
+    fn stand_in(statuses: &'static [u16]) -> (String, mpsc::Receiver<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            for (status, stream) in statuses.iter().zip(listener.incoming()) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut head = String::new();
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    head.push_str(&line.to_ascii_lowercase());
+                    line.clear();
+                }
+                let length = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("content-length:"))
+                    .map_or(0, |l| l.trim().parse().unwrap());
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )
+                .unwrap();
+                tx.send((head, String::from_utf8(body).unwrap())).unwrap();
+            }
+        });
+
+        (url, rx)
+    }
+
+    #[test]
+    fn retries_server_errors() {
+        let (url, requests) = stand_in(&[503, 200]);
+        let webhook = Webhook {
+            url,
+            headers: "Authorization: Bearer secret\n".to_string(),
+            ..Default::default()
+        };
+
+        webhook
+            .send_with_backoff("{\"a\":1}", Duration::from_millis(1))
+            .unwrap();
+
+        for _ in 0..2 {
+            let (head, body) = requests.recv().unwrap();
+            assert!(head.starts_with("post /hook "));
+            assert!(head.contains("authorization: bearer secret"));
+            assert!(head.contains("content-type: application/json"));
+            assert_eq!(body, "{\"a\":1}");
+        }
+    }
+
+    #[test]
+    fn gives_up_on_client_errors() {
+        let (url, requests) = stand_in(&[400, 200]);
+        let webhook = Webhook {
+            url,
+            ..Default::default()
+        };
+
+        assert!(
+            webhook
+                .send_with_backoff("{}", Duration::from_millis(1))
+                .is_err()
+        );
+        assert!(requests.recv().is_ok());
+        assert!(requests.try_recv().is_err());
+    }
+}
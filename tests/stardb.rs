@@ -0,0 +1,225 @@
+// The stardb client against a local mock of the API.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::mpsc,
+    thread,
+};
+
+use stardb_exporter::{
+    games::Game,
+    stardb::{Client, Error, Login, Session, SyncMode},
+};
+
+struct Request {
+    method: String,
+    path: String,
+    cookie: Option<String>,
+    body: String,
+}
+
+// Answers with `responses` (status, extra headers, body) in order and hands
+// back the requests it got.
+fn mock(responses: Vec<(u16, &'static str, &'static str)>) -> (Client, mpsc::Receiver<Request>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = Client::new(&format!("http://{}/api", listener.local_addr().unwrap()));
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        for ((status, headers, body), stream) in responses.into_iter().zip(listener.incoming()) {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let mut parts = line.split_whitespace();
+            let method = parts.next().unwrap().to_string();
+            let path = parts.next().unwrap().to_string();
+
+            let mut cookie = None;
+            let mut length = 0;
+            loop {
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+                let Some((name, value)) = line.trim().split_once(':') else {
+                    break;
+                };
+                match name.to_ascii_lowercase().as_str() {
+                    "cookie" => cookie = Some(value.trim().to_string()),
+                    "content-length" => length = value.trim().parse().unwrap(),
+                    _ => {}
+                }
+            }
+            let mut request_body = vec![0; length];
+            reader.read_exact(&mut request_body).unwrap();
+
+            write!(
+                stream,
+                "HTTP/1.1 {status} X\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+
+            tx.send(Request {
+                method,
+                path,
+                cookie,
+                body: String::from_utf8(request_body).unwrap(),
+            })
+            .unwrap();
+        }
+    });
+
+    (client, rx)
+}
+
+fn json(body: &str) -> Option<serde_json::Value> {
+    serde_json::from_str(body).ok()
+}
+
+#[test]
+fn login_keeps_the_session_cookie() {
+    let (client, requests) = mock(vec![(200, "Set-Cookie: id=abc; Path=/; HttpOnly\r\n", "")]);
+
+    let session = client
+        .login(&Login {
+            username: "kiana",
+            password: "hunter2",
+        })
+        .unwrap();
+    assert_eq!(
+        session,
+        Session {
+            cookie: "id=abc".to_string(),
+            username: "kiana".to_string(),
+        }
+    );
+
+    let request = requests.recv().unwrap();
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/api/users/auth/login");
+    assert_eq!(
+        json(&request.body),
+        Some(serde_json::json!({ "username": "kiana", "password": "hunter2" }))
+    );
+}
+
+#[test]
+fn wrong_credentials_are_a_validation_error() {
+    let (client, _requests) = mock(vec![(401, "", "")]);
+
+    let login = Login {
+        username: "kiana",
+        password: "wrong",
+    };
+    assert!(matches!(client.login(&login), Err(Error::Validation(_))));
+}
+
+#[test]
+fn renew_separates_expired_sessions() {
+    let (client, requests) = mock(vec![
+        (200, "Set-Cookie: id=new; Path=/\r\n", "\"kiana\""),
+        (401, "", ""),
+    ]);
+
+    let session = client.renew("id=old").unwrap();
+    assert_eq!(session.cookie, "id=new");
+    assert_eq!(session.username, "kiana");
+    assert_eq!(requests.recv().unwrap().cookie.as_deref(), Some("id=old"));
+
+    assert!(matches!(client.renew("id=new"), Err(Error::AuthExpired)));
+}
+
+#[test]
+fn import_pulls_reports_rejected_urls() {
+    let (client, requests) = mock(vec![
+        (200, "", "{\"uid\":800000000}"),
+        (400, "", "Invalid url"),
+        (500, "", ""),
+        (200, "", "not json"),
+    ]);
+
+    let url = "https://example.com/getGachaLog";
+
+    let import = client.import_pulls(None, Game::Zzz, url).unwrap();
+    assert_eq!(import.uid, 800_000_000);
+    let request = requests.recv().unwrap();
+    assert_eq!(request.path, "/api/zzz/signals-import");
+    assert_eq!(request.cookie, None);
+
+    assert!(matches!(
+        client.import_pulls(Some("id=abc"), Game::Hsr, url),
+        Err(Error::Validation(message)) if message == "Invalid url"
+    ));
+    assert!(matches!(
+        client.import_pulls(None, Game::Hsr, url),
+        Err(Error::Status(500))
+    ));
+    assert!(matches!(
+        client.import_pulls(None, Game::Hsr, url),
+        Err(Error::InvalidResponse(_))
+    ));
+}
+
+#[test]
+fn unreachable_servers_are_network_errors() {
+    // Nothing listens on the port once the listener is dropped.
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let client = Client::new(&format!("http://127.0.0.1:{port}/api"));
+
+    assert!(matches!(client.logout("id=abc"), Err(Error::Network(_))));
+}
+
+#[test]
+fn failed_mirror_takes_back_the_additions() {
+    let (client, requests) = mock(vec![
+        (200, "", "[1,2]"),
+        (200, "", ""),
+        (500, "", ""),
+        (200, "", ""),
+    ]);
+
+    let diff = client.preview_sync("id=abc", Game::Hsr, &[2, 3]).unwrap();
+    assert_eq!(diff.to_add, [3]);
+    assert_eq!(diff.to_remove, [1]);
+    assert_eq!(diff.unchanged, [2]);
+
+    assert!(matches!(
+        client.apply_sync("id=abc", Game::Hsr, &diff, SyncMode::Mirror),
+        Err(Error::Status(500))
+    ));
+
+    let calls: Vec<_> = requests
+        .iter()
+        .take(4)
+        .map(|r| (r.method, json(&r.body)))
+        .collect();
+    assert_eq!(
+        calls,
+        [
+            ("GET".to_string(), None),
+            ("PUT".to_string(), Some(serde_json::json!([3]))),
+            ("DELETE".to_string(), Some(serde_json::json!([1]))),
+            ("DELETE".to_string(), Some(serde_json::json!([3]))),
+        ]
+    );
+}
+
+#[test]
+fn add_only_never_removes() {
+    let (client, requests) = mock(vec![(200, "", "[1]"), (200, "", "")]);
+
+    let diff = client
+        .sync_achievements("id=abc", Game::Hsr, &[2], SyncMode::AddOnly)
+        .unwrap();
+    assert_eq!(diff.to_remove, [1]);
+
+    let methods: Vec<_> = requests.iter().take(2).map(|r| r.method).collect();
+    assert_eq!(methods, ["GET", "PUT"]);
+    assert!(requests.try_recv().is_err());
+}